@@ -0,0 +1,64 @@
+use core::ops::{Deref, DerefMut};
+
+macro_rules! aligned {
+    ($(#[$meta:meta])* $name:ident, $align:literal) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[repr(C, align($align))]
+        pub struct $name<T>(pub T);
+
+        impl<T> Deref for $name<T> {
+            type Target = T;
+
+            fn deref(&self) -> &T {
+                &self.0
+            }
+        }
+
+        impl<T> DerefMut for $name<T> {
+            fn deref_mut(&mut self) -> &mut T {
+                &mut self.0
+            }
+        }
+    };
+}
+
+aligned!(
+    /// A value aligned to 4 bytes, see [`Align32`].
+    Align4, 4
+);
+aligned!(
+    /// A value aligned to 8 bytes, see [`Align32`].
+    Align8, 8
+);
+aligned!(
+    /// A value aligned to 16 bytes, see [`Align32`].
+    Align16, 16
+);
+aligned!(
+    /// A value aligned to 32 bytes.
+    ///
+    /// The map stores keys and values side by side, so the address of a value depends on the size
+    /// of the key. As value type of a map, this wrapper places every value at an address that is a
+    /// multiple of 32, e.g. to use values directly as DMA buffers or to keep them on cache lines
+    /// of their own. Only the padding needed to reach the alignment is added to each entry. The
+    /// wrapper dereferences to the inner value.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::{Align32, FcHashMap};
+    ///
+    /// let mut buffers = FcHashMap::<u8, Align32<[u8; 64]>, 4>::new();
+    /// buffers.insert(1, Align32([0; 64])).unwrap();
+    ///
+    /// let buffer = buffers.get_mut(&1).unwrap();
+    /// assert_eq!(buffer.as_ptr() as usize % 32, 0);
+    /// buffer[0] = 0xaa;
+    /// ```
+    Align32, 32
+);
+aligned!(
+    /// A value aligned to 64 bytes, see [`Align32`].
+    Align64, 64
+);
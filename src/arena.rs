@@ -0,0 +1,145 @@
+use crate::FcHashMap;
+use core::borrow::Borrow;
+use hash32::{Hash, Hasher};
+
+/// A small handle to a key interned in a [`KeyArena`].
+///
+/// Handles are 2 bytes in size and hash well, so they are cheap keys for any number of maps that
+/// share the same set of large keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct KeyHandle(u16);
+
+impl KeyHandle {
+    /// Returns the index of the key in its arena.
+    pub fn index(&self) -> usize {
+        usize::from(self.0)
+    }
+}
+
+impl Hash for KeyHandle {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+/// Storage for large keys that are shared by several maps.
+///
+/// Every key is stored once in the arena, which hands out a [`KeyHandle`] for it. Maps then use
+/// the handle instead of the key, e.g. `FcHashMap<KeyHandle, V, N>`. Keys can't be removed from
+/// the arena individually, so a handle stays valid until the arena is cleared. A handle is only
+/// valid for the arena that handed it out, which is not checked.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::{FcHashMap, KeyArena, KeyHandle};
+///
+/// let mut ids = KeyArena::<[u8; 32], 64>::new();
+/// let mut names = FcHashMap::<KeyHandle, &str, 64>::new();
+/// let mut rssi = FcHashMap::<KeyHandle, i8, 64>::new();
+///
+/// let id = ids.intern([0xab; 32]).unwrap();
+/// names.insert(id, "gateway").unwrap();
+/// rssi.insert(id, -67).unwrap();
+///
+/// // The same key results in the same handle
+/// assert_eq!(ids.intern([0xab; 32]), Ok(id));
+/// assert_eq!(ids.get_in(&names, &[0xab; 32]), Some(&"gateway"));
+/// assert_eq!(ids.key(id), &[0xab; 32]);
+/// ```
+pub struct KeyArena<K, const CAP: usize> {
+    keys: FcHashMap<K, (), CAP>,
+}
+
+impl<K, const CAP: usize> KeyArena<K, CAP> {
+    /// Creates an empty arena.
+    pub fn new() -> Self {
+        KeyArena {
+            keys: FcHashMap::new(),
+        }
+    }
+
+    /// Returns the number of keys the arena can hold.
+    pub fn capacity(&self) -> usize {
+        CAP
+    }
+
+    /// Removes all keys. All handles of this arena become invalid.
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    /// Looks up `key` in the arena and then the value of its handle in `map`.
+    pub fn get_in<'m, Q, V, const N: usize>(
+        &self,
+        map: &'m FcHashMap<KeyHandle, V, N>,
+        key: &Q,
+    ) -> Option<&'m V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.handle(key).and_then(|handle| map.get(&handle))
+    }
+
+    /// Returns the handle of a key, if the key is in the arena.
+    pub fn handle<Q>(&self, key: &Q) -> Option<KeyHandle>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.keys
+            .map
+            .find(key)
+            .map(|(_, b_idx)| KeyHandle(b_idx as u16))
+    }
+
+    /// Returns the handle of a key and adds the key to the arena, if it is not yet there.
+    ///
+    /// If the arena is full, the key is given back as error.
+    pub fn intern(&mut self, key: K) -> Result<KeyHandle, K>
+    where
+        K: Hash + Eq,
+    {
+        if let Some(handle) = self.handle(&key) {
+            return Ok(handle);
+        }
+        let handle = KeyHandle(self.keys.len() as u16);
+        // New keys are appended at the end, so the handle is the index of the new key
+        self.keys.insert(key, ()).map_err(|(key, _)| key)?;
+        Ok(handle)
+    }
+
+    /// Returns true if the arena contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Returns the key of a handle.
+    ///
+    /// A handle is only the index of its key, to keep it at 2 bytes, so the arena can't tell
+    /// whether a handle belongs to it. A handle of another arena, or from before `clear()`,
+    /// returns the key at its index in this arena. If there is no key at that index, this panics.
+    pub fn key(&self, handle: KeyHandle) -> &K {
+        &self.keys.map.buckets[handle.index()].key
+    }
+
+    /// Return an iterator over the handles and keys of the arena.
+    pub fn iter(&self) -> impl Iterator<Item = (KeyHandle, &K)> {
+        self.keys
+            .keys()
+            .enumerate()
+            .map(|(idx, key)| (KeyHandle(idx as u16), key))
+    }
+
+    /// Return the number of keys in the arena.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+}
+
+impl<K, const CAP: usize> Default for KeyArena<K, CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,54 @@
+/// Worst case numbers of elementary steps of one operation on the map.
+///
+/// Returned by [`FcHashMap::worst_case_ops()`](crate::FcHashMap::worst_case_ops). Multiplied
+/// with the time of a step on the target, the bounds give the longest time an operation can take,
+/// e.g. to size a critical section around a shared map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpBounds {
+    /// Places of the hash table read while searching a key, by `get()`, `insert()` and `remove()`.
+    pub probes: usize,
+    /// Full comparisons of keys with the same hash value while searching a key.
+    pub key_comparisons: usize,
+    /// Places of the hash table moved by `insert()` or `remove()`.
+    pub shifts: usize,
+}
+
+/// Numbers of places of the hash table read by lookups, measured on the current content of a map.
+///
+/// Returned by [`FcHashMap::probe_stats()`](crate::FcHashMap::probe_stats). In contrast to
+/// [`OpBounds`], which hold for any keys, these are the actual numbers for the keys in the map.
+/// A lookup of a key in the map reads the places from the desired place of the key up to its
+/// place. A lookup of a missing key reads the places from its desired place up to the first
+/// empty place.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProbeStats {
+    /// The number of key-value pairs in the map.
+    pub len: usize,
+    /// The number of places of the hash table.
+    pub capacity: usize,
+    /// The sum of the places read by lookups of all keys in the map.
+    pub hit_probes: usize,
+    /// The largest number of places read by a lookup of a key in the map.
+    pub max_hit_probes: usize,
+    /// The sum of the places read by lookups of missing keys, one for each desired place.
+    pub miss_probes: usize,
+    /// The largest number of places read by a lookup of a missing key.
+    pub max_miss_probes: usize,
+}
+
+impl ProbeStats {
+    /// Returns the mean number of places read by a lookup of a key in the map.
+    pub fn mean_hit_probes(&self) -> f32 {
+        if self.len == 0 {
+            0.0
+        } else {
+            self.hit_probes as f32 / self.len as f32
+        }
+    }
+
+    /// Returns the mean number of places read by a lookup of a missing key, for keys whose hash
+    /// values spread evenly.
+    pub fn mean_miss_probes(&self) -> f32 {
+        self.miss_probes as f32 / self.capacity as f32
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{map::Map, FcHashMap};
+use hash32::Hash;
+
+/// A builder that constructs a map from unique keys without probing.
+///
+/// Entries are first collected without touching the hash table. [`finish()`](BulkLoader::finish)
+/// then sorts them by their desired place in the hash table and lays out the table in a single
+/// pass. This results in the same optimal displacement as inserting them one by one, but is
+/// considerably faster for large tables that are constructed at boot time.
+///
+/// The keys must be unique. This is checked in debug builds only. After `finish()` the entries of
+/// the map are ordered by their hash values instead of their order of insertion.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::{BulkLoader, FcHashMap};
+///
+/// let mut loader = BulkLoader::<u32, u32, 1024>::new();
+/// for key in 0..800 {
+///     loader.push(key, key * 2).unwrap();
+/// }
+/// let map: FcHashMap<u32, u32, 1024> = loader.finish();
+///
+/// assert_eq!(map.len(), 800);
+/// assert_eq!(map.get(&400), Some(&800));
+/// ```
+pub struct BulkLoader<K, V, const CAP: usize> {
+    map: Map<K, V, CAP>,
+}
+
+impl<K, V, const CAP: usize> BulkLoader<K, V, CAP> {
+    /// Creates an empty bulk loader.
+    pub fn new() -> Self {
+        BulkLoader { map: Map::new() }
+    }
+
+    /// Returns the number of entries pushed so far.
+    pub fn len(&self) -> usize {
+        self.map.buckets.len()
+    }
+
+    /// Returns true if no entries were pushed so far.
+    pub fn is_empty(&self) -> bool {
+        self.map.buckets.is_empty()
+    }
+
+    /// Adds an entry to the loader.
+    ///
+    /// If the loader already holds `CAP` entries, the key-value pair is given back as error.
+    pub fn push(&mut self, key: K, value: V) -> Result<(), (K, V)>
+    where
+        K: Hash,
+    {
+        self.map.push_unindexed(key, value)
+    }
+
+    /// Builds the map from the collected entries.
+    pub fn finish(mut self) -> FcHashMap<K, V, CAP>
+    where
+        K: Hash + Eq,
+    {
+        self.map.build_sorted_hash_table();
+        debug_assert!(
+            self.map
+                .buckets
+                .iter()
+                .enumerate()
+                .all(|(b_idx, bucket)| self.map.find(&bucket.key).map(|(_, b)| b) == Some(b_idx)),
+            "BulkLoader: keys are not unique"
+        );
+        FcHashMap { map: self.map }
+    }
+}
+
+impl<K, V, const CAP: usize> Default for BulkLoader<K, V, CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
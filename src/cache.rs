@@ -0,0 +1,223 @@
+use crate::FcHashMap;
+use core::borrow::Borrow;
+use hash32::Hash;
+
+// The expiry time and the last use of a cache entry
+#[derive(Clone, Copy)]
+struct Stamp {
+    expires_at: u32,
+    last_use: u32,
+}
+
+impl Stamp {
+    // Check if the entry has expired at the time now. The times are wrapping tick counters, so
+    // they are compared by their difference.
+    fn is_expired(&self, now: u32) -> bool {
+        (now.wrapping_sub(self.expires_at) as i32) >= 0
+    }
+}
+
+/// A cache whose entries expire after a time to live, and whose least recently used entry is
+/// evicted if it is full.
+///
+/// Neighbor or DNS caches need both: an entry must not be used after it expired, and a new entry
+/// must find room even if no entry has expired yet. If the cache is full, an expired entry is
+/// evicted if there is one, otherwise the least recently used entry.
+///
+/// The cache has no clock of its own. Every operation that depends on the time is given the
+/// current time `now` in ticks of any unit, e.g. milliseconds of a system timer. The tick
+/// counter may wrap around, but a time to live must be less than 2^31 ticks. Expired entries are
+/// treated as absent and removed when they are looked up, or all at once with
+/// [`purge_expired()`](FcLruTtlCache::purge_expired). Until then, they count in `len()`.
+///
+/// Finding the entry to evict is a linear scan over all entries, so an insert into a full cache
+/// takes time linear in `CAP`. In return, an entry only carries two tick counters and no links
+/// of a recency list. The eviction policy is fixed, it is not a type parameter.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::FcLruTtlCache;
+///
+/// // IP address -> MAC address
+/// let mut neighbors = FcLruTtlCache::<u32, [u8; 6], 2>::new();
+/// neighbors.insert(0x0a00_0001, [2, 0, 0, 0, 0, 1], 0, 1000);
+/// neighbors.insert(0x0a00_0002, [2, 0, 0, 0, 0, 2], 10, 1000);
+///
+/// // The first entry is used, so the second one is evicted for a third one
+/// assert!(neighbors.get(&0x0a00_0001, 20).is_some());
+/// let evicted = neighbors.insert(0x0a00_0003, [2, 0, 0, 0, 0, 3], 30, 1000);
+/// assert_eq!(evicted.map(|(ip, _)| ip), Some(0x0a00_0002));
+///
+/// // After its time to live, an entry is gone
+/// assert!(neighbors.get(&0x0a00_0001, 1000).is_none());
+/// ```
+pub struct FcLruTtlCache<K, V, const CAP: usize> {
+    map: FcHashMap<K, (V, Stamp), CAP>,
+    clock: u32,
+}
+
+impl<K, V, const CAP: usize> FcLruTtlCache<K, V, CAP> {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        FcLruTtlCache {
+            map: FcHashMap::new(),
+            clock: 0,
+        }
+    }
+
+    /// Returns the number of entries the cache can hold.
+    pub fn capacity(&self) -> usize {
+        CAP
+    }
+
+    /// Removes all entries from the cache.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns true if the cache contains an entry for the key that has not expired at `now`.
+    /// The entry does not count as used.
+    pub fn contains_key<Q>(&self, key: &Q, now: u32) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.peek(key, now).is_some()
+    }
+
+    /// Returns a reference to the value of the key and marks the entry as used. An entry that has
+    /// expired at `now` is removed and `None` is returned.
+    pub fn get<Q>(&mut self, key: &Q, now: u32) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.get_mut(key, now).map(|v| &*v)
+    }
+
+    /// Returns a mutable reference to the value of the key and marks the entry as used. An entry
+    /// that has expired at `now` is removed and `None` is returned.
+    pub fn get_mut<Q>(&mut self, key: &Q, now: u32) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (h_idx, b_idx) = self.map.map.find(key)?;
+        if self.map.map.buckets[b_idx].value.1.is_expired(now) {
+            self.map.map.remove_found(h_idx, b_idx);
+            return None;
+        }
+        let last_use = self.tick();
+        let (value, stamp) = &mut self.map.map.buckets[b_idx].value;
+        stamp.last_use = last_use;
+        Some(value)
+    }
+
+    /// Inserts a key-value pair that expires `ttl` ticks after `now`, and marks it as used.
+    ///
+    /// Returns the entry that was pushed out of the cache: if the key was already in the cache,
+    /// the key with its old value, otherwise the evicted entry if the cache was full. An expired
+    /// entry is evicted in preference to the least recently used one.
+    pub fn insert(&mut self, key: K, value: V, now: u32, ttl: u32) -> Option<(K, V)>
+    where
+        K: Hash + Eq,
+    {
+        match self.map.map.find_or_hash(&key) {
+            Ok((_, b_idx)) => {
+                let stamp = self.stamp(now, ttl);
+                let (v, s) = &mut self.map.map.buckets[b_idx].value;
+                *s = stamp;
+                Some((key, core::mem::replace(v, value)))
+            }
+            Err(hash) => {
+                // A capacity of 0 is rejected at compile time, so a full cache has a victim and
+                // the insert after its eviction succeeds
+                let evicted = if self.map.len() == CAP {
+                    self.victim(now).map(|b_idx| {
+                        let h_idx = self.map.map.h_idx_of(b_idx);
+                        let (k, (v, _)) = self.map.map.remove_found(h_idx, b_idx);
+                        (k, v)
+                    })
+                } else {
+                    None
+                };
+                let stamp = self.stamp(now, ttl);
+                let _ = self.map.map.insert_hashed(key, (value, stamp), hash);
+                evicted
+            }
+        }
+    }
+
+    /// Returns true if the cache contains no entries.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return the number of entries in the cache, including expired entries that have not been
+    /// removed yet.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns a reference to the value of the key, without marking the entry as used. Returns
+    /// `None` if the entry has expired at `now`.
+    pub fn peek<Q>(&self, key: &Q, now: u32) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        match self.map.get(key) {
+            Some((value, stamp)) if !stamp.is_expired(now) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Removes all entries that have expired at `now` and returns their number.
+    pub fn purge_expired(&mut self, now: u32) -> usize {
+        let len = self.map.len();
+        self.map.retain(|_, (_, stamp)| !stamp.is_expired(now));
+        len - self.map.len()
+    }
+
+    /// Removes a key from the cache, returning its value if the key was in the cache, even if
+    /// the entry has expired.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.remove(key).map(|(v, _)| v)
+    }
+
+    // Stamp an entry that is used now and expires ttl ticks later
+    fn stamp(&mut self, now: u32, ttl: u32) -> Stamp {
+        Stamp {
+            expires_at: now.wrapping_add(ttl),
+            last_use: self.tick(),
+        }
+    }
+
+    // Advance the use counter and return its new value
+    fn tick(&mut self) -> u32 {
+        self.clock = self.clock.wrapping_add(1);
+        self.clock
+    }
+
+    // Find the entry to evict: an expired one, or the least recently used one
+    fn victim(&self, now: u32) -> Option<usize> {
+        let clock = self.clock;
+        self.map
+            .iter_indexed()
+            .max_by_key(|(_, _, (_, stamp))| {
+                (stamp.is_expired(now), clock.wrapping_sub(stamp.last_use))
+            })
+            .map(|(b_idx, _, _)| b_idx)
+    }
+}
+
+impl<K, V, const CAP: usize> Default for FcLruTtlCache<K, V, CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
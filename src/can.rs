@@ -0,0 +1,241 @@
+//! Key types and maps for CAN and J1939.
+//!
+//! CAN identifiers are small integers, but they are not spread evenly: the identifiers of a bus
+//! are often assigned in blocks, and J1939 packs priority, parameter group and source address
+//! into one 29 bit identifier. The key types of this module validate the ranges of the
+//! identifiers and mix all of their bits (see [`mix32()`](crate::key::mix32)), so they spread
+//! well over the hash table of a [`FcHashMap`](crate::FcHashMap).
+//!
+//! The 11 bit identifier space is small enough for a table with a place for every identifier.
+//! [`StandardIdMap`] needs no hashing at all and finds a value with a single index operation.
+//!
+//! Only available with the `can` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use fchashmap::can::{ExtendedId, J1939Key, StandardId, StandardIdMap};
+//! use fchashmap::FcHashMap;
+//!
+//! let mut last_frame = StandardIdMap::<[u8; 8]>::new();
+//! let id = StandardId::new(0x123).unwrap();
+//! last_frame.insert(id, [1, 2, 3, 4, 5, 6, 7, 8]);
+//! assert_eq!(last_frame.get(id).map(|data| data[0]), Some(1));
+//!
+//! // Engine speed (PGN 61444) from source address 0
+//! let id = ExtendedId::new(0x0cf0_0400).unwrap();
+//! let mut signals = FcHashMap::<J1939Key, u16, 64>::new();
+//! signals.insert(J1939Key::from_id(id), 1500).unwrap();
+//! assert_eq!(signals.get(&J1939Key::new(61444, 0x00).unwrap()), Some(&1500));
+//! ```
+use crate::key::mix32;
+use hash32::{Hash, Hasher};
+
+/// An 11 bit CAN identifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct StandardId(u16);
+
+impl StandardId {
+    /// The largest standard identifier.
+    pub const MAX: StandardId = StandardId(0x7ff);
+
+    /// Creates an identifier, or returns `None` if `raw` has more than 11 bits.
+    pub const fn new(raw: u16) -> Option<Self> {
+        if raw <= Self::MAX.0 {
+            Some(StandardId(raw))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the identifier as integer.
+    pub const fn as_raw(&self) -> u16 {
+        self.0
+    }
+}
+
+impl Hash for StandardId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(&mix32(u32::from(self.0)).to_le_bytes());
+    }
+}
+
+/// A 29 bit CAN identifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ExtendedId(u32);
+
+impl ExtendedId {
+    /// The largest extended identifier.
+    pub const MAX: ExtendedId = ExtendedId(0x1fff_ffff);
+
+    /// Creates an identifier, or returns `None` if `raw` has more than 29 bits.
+    pub const fn new(raw: u32) -> Option<Self> {
+        if raw <= Self::MAX.0 {
+            Some(ExtendedId(raw))
+        } else {
+            None
+        }
+    }
+
+    /// Returns the identifier as integer.
+    pub const fn as_raw(&self) -> u32 {
+        self.0
+    }
+}
+
+impl Hash for ExtendedId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(&mix32(self.0).to_le_bytes());
+    }
+}
+
+/// A J1939 parameter group number (PGN) together with the source address of its sender.
+///
+/// The priority of a frame is not part of the key, as it does not change the meaning of the
+/// frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct J1939Key {
+    pgn: u32,
+    source_address: u8,
+}
+
+impl J1939Key {
+    /// Creates a key, or returns `None` if `pgn` has more than 18 bits.
+    pub const fn new(pgn: u32, source_address: u8) -> Option<Self> {
+        if pgn <= 0x3ffff {
+            Some(J1939Key {
+                pgn,
+                source_address,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Extracts the key from the identifier of a J1939 frame.
+    ///
+    /// For a destination specific parameter group (PDU1 format, PF below 240), the PDU specific
+    /// field holds the destination address, which is not part of the PGN.
+    pub const fn from_id(id: ExtendedId) -> Self {
+        let mut pgn = (id.0 >> 8) & 0x3ffff;
+        if (pgn >> 8) & 0xff < 240 {
+            pgn &= 0x3ff00;
+        }
+        J1939Key {
+            pgn,
+            source_address: id.0 as u8,
+        }
+    }
+
+    /// Returns the parameter group number.
+    pub const fn pgn(&self) -> u32 {
+        self.pgn
+    }
+
+    /// Returns the source address.
+    pub const fn source_address(&self) -> u8 {
+        self.source_address
+    }
+}
+
+impl Hash for J1939Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let packed = self.pgn << 8 | u32::from(self.source_address);
+        state.write(&mix32(packed).to_le_bytes());
+    }
+}
+
+/// A map with a place for every 11 bit CAN identifier.
+///
+/// The value of an identifier is found by indexing, without hashing and probing, and the map can
+/// never be full. It takes 2048 times the size of `Option<V>`, so it pays off for small values
+/// or for buses on which many identifiers are in use. The iteration order is the order of the
+/// identifiers.
+pub struct StandardIdMap<V> {
+    values: [Option<V>; 2048],
+    len: usize,
+}
+
+impl<V> StandardIdMap<V> {
+    // An empty place of the map
+    const EMPTY: Option<V> = None;
+
+    /// Creates an empty map.
+    pub const fn new() -> Self {
+        StandardIdMap {
+            values: [Self::EMPTY; 2048],
+            len: 0,
+        }
+    }
+
+    /// Returns the number of identifiers the map can hold, which is 2048.
+    pub fn capacity(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Removes all identifiers and values in the map.
+    pub fn clear(&mut self) {
+        for value in self.values.iter_mut() {
+            *value = None;
+        }
+        self.len = 0;
+    }
+
+    /// Returns true if the map contains a value for the identifier.
+    pub fn contains_key(&self, id: StandardId) -> bool {
+        self.values[usize::from(id.0)].is_some()
+    }
+
+    /// Returns a reference to the value of the identifier.
+    pub fn get(&self, id: StandardId) -> Option<&V> {
+        self.values[usize::from(id.0)].as_ref()
+    }
+
+    /// Returns a mutable reference to the value of the identifier.
+    pub fn get_mut(&mut self, id: StandardId) -> Option<&mut V> {
+        self.values[usize::from(id.0)].as_mut()
+    }
+
+    /// Inserts a value for the identifier and returns the old value, if there was one.
+    pub fn insert(&mut self, id: StandardId, value: V) -> Option<V> {
+        let old = self.values[usize::from(id.0)].replace(value);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+
+    /// Returns true if the map contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return an iterator over the identifiers and values of the map, in ascending order of the
+    /// identifiers.
+    pub fn iter(&self) -> impl Iterator<Item = (StandardId, &V)> {
+        self.values
+            .iter()
+            .enumerate()
+            .filter_map(|(id, value)| value.as_ref().map(|v| (StandardId(id as u16), v)))
+    }
+
+    /// Return the number of values in the map.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Removes the value of the identifier and returns it, if there was one.
+    pub fn remove(&mut self, id: StandardId) -> Option<V> {
+        let old = self.values[usize::from(id.0)].take();
+        if old.is_some() {
+            self.len -= 1;
+        }
+        old
+    }
+}
+
+impl<V> Default for StandardIdMap<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
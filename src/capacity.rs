@@ -0,0 +1,40 @@
+/// Returns the capacity of a map that holds at least `entries` entries.
+///
+/// The capacity of [`FcHashMap`](crate::FcHashMap) must be a power of 2, so `entries` is rounded
+/// up to the next one. This is a `const fn`, so capacities can be derived from configuration
+/// constants at compile time, see [`fc_map_type!`](crate::fc_map_type).
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::{capacity_for, FcHashMap};
+///
+/// const MAX_NODES: usize = 100;
+///
+/// let nodes = FcHashMap::<u16, u8, { capacity_for(MAX_NODES) }>::new();
+/// assert_eq!(nodes.capacity(), 128);
+/// assert_eq!(capacity_for(128), 128);
+/// ```
+pub const fn capacity_for(entries: usize) -> usize {
+    entries.next_power_of_two()
+}
+
+/// Returns the capacity of a map that holds `expected_items` entries at a load of at most 80%.
+///
+/// Above this load, the map gets slow (see [Performance](crate::FcHashMap#performance)), so
+/// `expected_items` is divided by 0.8 and rounded up to the next power of 2. Like
+/// [`capacity_for()`], this is a `const fn` for the capacity parameter of a map.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::{recommended_capacity, FcHashMap};
+///
+/// let map = FcHashMap::<u32, u32, { recommended_capacity(600) }>::new();
+/// assert_eq!(map.capacity(), 1024);
+/// assert_eq!(recommended_capacity(800), 1024);
+/// assert_eq!(recommended_capacity(820), 2048);
+/// ```
+pub const fn recommended_capacity(expected_items: usize) -> usize {
+    capacity_for((expected_items * 5).div_ceil(4))
+}
@@ -0,0 +1,13 @@
+/// What to do if a transformation of a map produces the same key twice.
+///
+/// Used by [`FcHashMap::invert()`](crate::FcHashMap::invert). "First" and "last" refer to the
+/// order of the map, as returned by [`FcHashMap::iter()`](crate::FcHashMap::iter).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Duplicates {
+    /// Fail with a [`CollisionError`](crate::CollisionError).
+    Reject,
+    /// Keep the entry that comes first.
+    KeepFirst,
+    /// Keep the entry that comes last.
+    KeepLast,
+}
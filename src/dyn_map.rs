@@ -0,0 +1,183 @@
+use crate::FcHashMap;
+use hash32::Hash;
+
+/// An object safe interface to maps of any capacity.
+///
+/// Each capacity of [`FcHashMap`] is a type of its own, and every function that takes a map is
+/// monomorphized for every capacity it is used with. With `&dyn DynFixedMap<K, V>` or
+/// `&mut dyn DynFixedMap<K, V>`, code can be written once for all capacities, e.g. for plugins
+/// or board variants whose maps differ in size only.
+///
+/// Iteration is done with callbacks, as iterator types can't be part of an object safe trait.
+/// The size of a map is known without touching its elements: `len()`, `capacity()` and
+/// `is_empty()` take constant time, and [`load()`](DynFixedMap::load) returns all of it with a
+/// single virtual call, e.g. for health monitoring over many maps.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::{DynFixedMap, FcHashMap};
+///
+/// fn register(map: &mut dyn DynFixedMap<u32, &'static str>) {
+///     map.insert(1, "sensor").unwrap();
+/// }
+///
+/// let mut small = FcHashMap::<u32, &str, 4>::new();
+/// let mut large = FcHashMap::<u32, &str, 64>::new();
+/// register(&mut small);
+/// register(&mut large);
+///
+/// let maps: [&dyn DynFixedMap<u32, &str>; 2] = [&small, &large];
+/// let mut count = 0;
+/// for map in maps.iter() {
+///     map.for_each(&mut |_, _| count += 1);
+/// }
+/// assert_eq!(count, 2);
+/// ```
+pub trait DynFixedMap<K, V> {
+    /// Returns the number of elements the map can hold.
+    fn capacity(&self) -> usize;
+
+    /// Remove all key-value pairs in the map.
+    fn clear(&mut self);
+
+    /// Calls `f` for every key-value pair of the map, in the order of the map.
+    fn for_each(&self, f: &mut dyn FnMut(&K, &V));
+
+    /// Calls `f` for every key and a mutable reference to its value, in the order of the map.
+    fn for_each_mut(&mut self, f: &mut dyn FnMut(&K, &mut V));
+
+    /// Returns a reference to the value corresponding to the key.
+    fn get(&self, key: &K) -> Option<&V>;
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    fn get_mut(&mut self, key: &K) -> Option<&mut V>;
+
+    /// Returns a mutable reference to the value of the key, inserting the value calculated by
+    /// `default` if the key is not in the map. This is the entry API of
+    /// [`FcHashMap::entry()`] in an object safe form, with a single search in the map.
+    ///
+    /// If the key is not in the map and the map is full, the key is given back as error.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::{DynFixedMap, FcHashMap};
+    ///
+    /// fn count(map: &mut dyn DynFixedMap<&'static str, u32>, word: &'static str) {
+    ///     *map.get_or_insert_with(word, &mut || 0).unwrap() += 1;
+    /// }
+    ///
+    /// let mut words = FcHashMap::<&str, u32, 4>::new();
+    /// for word in ["a", "b", "a"].iter() {
+    ///     count(&mut words, word);
+    /// }
+    /// assert_eq!(words.get(&"a"), Some(&2));
+    /// ```
+    fn get_or_insert_with(&mut self, key: K, default: &mut dyn FnMut() -> V) -> Result<&mut V, K>;
+
+    /// Inserts a key-value pair into the map, see [`FcHashMap::insert()`].
+    fn insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)>;
+
+    /// Returns true if the map contains no elements.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Return the number of key-value pairs in the map.
+    fn len(&self) -> usize;
+
+    /// Returns the number of key-value pairs and the capacity of the map at once.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::{DynFixedMap, FcHashMap};
+    ///
+    /// let mut map = FcHashMap::<u8, u8, 8>::new();
+    /// map.insert(1, 1).unwrap();
+    /// map.insert(2, 2).unwrap();
+    ///
+    /// let load = (&map as &dyn DynFixedMap<u8, u8>).load();
+    /// assert_eq!((load.len, load.capacity, load.percent()), (2, 8, 25));
+    /// ```
+    fn load(&self) -> MapLoad {
+        MapLoad {
+            len: self.len(),
+            capacity: self.capacity(),
+        }
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously
+    /// in the map.
+    fn remove(&mut self, key: &K) -> Option<V>;
+}
+
+/// The number of key-value pairs and the capacity of a map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MapLoad {
+    /// The number of key-value pairs in the map.
+    pub len: usize,
+    /// The number of elements the map can hold.
+    pub capacity: usize,
+}
+
+impl MapLoad {
+    /// Returns the load of the map in percent of its capacity, rounded down.
+    pub fn percent(&self) -> usize {
+        self.len * 100 / self.capacity
+    }
+}
+
+impl<K, V, const CAP: usize> DynFixedMap<K, V> for FcHashMap<K, V, CAP>
+where
+    K: Eq + Hash,
+{
+    fn capacity(&self) -> usize {
+        FcHashMap::capacity(self)
+    }
+
+    fn clear(&mut self) {
+        FcHashMap::clear(self)
+    }
+
+    fn for_each(&self, f: &mut dyn FnMut(&K, &V)) {
+        for (key, value) in self.iter() {
+            f(key, value);
+        }
+    }
+
+    fn for_each_mut(&mut self, f: &mut dyn FnMut(&K, &mut V)) {
+        for (key, value) in self.iter_mut() {
+            f(key, value);
+        }
+    }
+
+    fn get(&self, key: &K) -> Option<&V> {
+        FcHashMap::get(self, key)
+    }
+
+    fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        FcHashMap::get_mut(self, key)
+    }
+
+    fn get_or_insert_with(&mut self, key: K, default: &mut dyn FnMut() -> V) -> Result<&mut V, K> {
+        Ok(self.entry(key)?.or_insert_with(default))
+    }
+
+    fn insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)> {
+        FcHashMap::insert(self, key, value)
+    }
+
+    fn is_empty(&self) -> bool {
+        FcHashMap::is_empty(self)
+    }
+
+    fn len(&self) -> usize {
+        FcHashMap::len(self)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        FcHashMap::remove(self, key)
+    }
+}
@@ -0,0 +1,149 @@
+use crate::map::{HashValue, Map};
+use core::mem;
+
+/// A view into a single entry of a map, which is either occupied or vacant.
+///
+/// Created by [`FcHashMap::entry()`](crate::FcHashMap::entry).
+pub enum Entry<'a, K, V, const CAP: usize> {
+    /// An entry whose key is in the map.
+    Occupied(OccupiedEntry<'a, K, V, CAP>),
+    /// An entry whose key is not in the map. There is room for it.
+    Vacant(VacantEntry<'a, K, V, CAP>),
+}
+
+impl<'a, K, V, const CAP: usize> Entry<'a, K, V, CAP>
+where
+    K: PartialEq,
+{
+    /// Calls `f` with the value of an occupied entry and returns the entry.
+    pub fn and_modify<F>(mut self, f: F) -> Self
+    where
+        F: FnOnce(&mut V),
+    {
+        if let Entry::Occupied(ref mut entry) = self {
+            f(entry.get_mut());
+        }
+        self
+    }
+
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Inserts the default value if the entry is vacant and returns a mutable reference to the
+    /// value of the entry.
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    /// Inserts `default` if the entry is vacant and returns a mutable reference to the value of
+    /// the entry.
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    /// Inserts the result of `default` if the entry is vacant and returns a mutable reference to
+    /// the value of the entry.
+    pub fn or_insert_with<F>(self, default: F) -> &'a mut V
+    where
+        F: FnOnce() -> V,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+}
+
+/// A view into an entry whose key is in the map.
+pub struct OccupiedEntry<'a, K, V, const CAP: usize> {
+    map: &'a mut Map<K, V, CAP>,
+    h_idx: usize,
+    b_idx: usize,
+}
+
+impl<'a, K, V, const CAP: usize> OccupiedEntry<'a, K, V, CAP> {
+    pub(crate) fn new(map: &'a mut Map<K, V, CAP>, h_idx: usize, b_idx: usize) -> Self {
+        OccupiedEntry { map, h_idx, b_idx }
+    }
+
+    /// Returns a reference to the value of the entry.
+    pub fn get(&self) -> &V {
+        &self.map.buckets[self.b_idx].value
+    }
+
+    /// Returns a mutable reference to the value of the entry.
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.buckets[self.b_idx].value
+    }
+
+    /// Replaces the value of the entry and returns the old value.
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    /// Converts the entry into a mutable reference to its value, which lives as long as the map
+    /// is borrowed.
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.buckets[self.b_idx].value
+    }
+
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        &self.map.buckets[self.b_idx].key
+    }
+
+    /// Removes the entry from the map and returns its value.
+    pub fn remove(self) -> V {
+        self.remove_entry().1
+    }
+
+    /// Removes the entry from the map and returns its key and value.
+    pub fn remove_entry(self) -> (K, V) {
+        self.map.remove_found(self.h_idx, self.b_idx)
+    }
+}
+
+/// A view into an entry whose key is not in the map.
+///
+/// There is always room for the entry, as [`FcHashMap::entry()`](crate::FcHashMap::entry)
+/// returns an error for absent keys if the map is full.
+pub struct VacantEntry<'a, K, V, const CAP: usize> {
+    map: &'a mut Map<K, V, CAP>,
+    key: K,
+    hash: HashValue,
+}
+
+impl<'a, K, V, const CAP: usize> VacantEntry<'a, K, V, CAP>
+where
+    K: PartialEq,
+{
+    pub(crate) fn new(map: &'a mut Map<K, V, CAP>, key: K, hash: HashValue) -> Self {
+        VacantEntry { map, key, hash }
+    }
+
+    /// Inserts the value into the map and returns a mutable reference to it.
+    pub fn insert(self, value: V) -> &'a mut V {
+        // The key is not in the map and the map is not full, so it always becomes a new bucket
+        // at the end of the bucket list
+        let _ = self.map.insert_hashed(self.key, value, self.hash);
+        &mut self.map.buckets.last_mut().unwrap().value
+    }
+
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    /// Returns the key of the entry, without inserting anything.
+    pub fn into_key(self) -> K {
+        self.key
+    }
+}
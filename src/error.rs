@@ -0,0 +1,163 @@
+use core::{error::Error, fmt};
+
+/// The error of a transformation whose key mapping produced the same key twice.
+///
+/// Returned by [`FcHashMap::map_keys()`](crate::FcHashMap::map_keys).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CollisionError;
+
+impl fmt::Display for CollisionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("two keys were mapped to the same key")
+    }
+}
+
+impl Error for CollisionError {}
+
+/// The error of [`FcHashMap::try_insert()`](crate::FcHashMap::try_insert).
+///
+/// The rejected key-value pair is given back in both cases.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TryInsertError<'a, K, V> {
+    /// The key is already in the map, `existing` is its value in the map.
+    Occupied { key: K, value: V, existing: &'a V },
+    /// The key is not in the map, but the map is full.
+    Full { key: K, value: V },
+}
+
+impl<'a, K, V> fmt::Display for TryInsertError<'a, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryInsertError::Occupied { .. } => f.write_str("the key is already in the map"),
+            TryInsertError::Full { .. } => f.write_str("the map is full"),
+        }
+    }
+}
+
+impl<'a, K: fmt::Debug, V: fmt::Debug> Error for TryInsertError<'a, K, V> {}
+
+/// The error of building a map from a slice.
+///
+/// Returned by the `TryFrom<&[(K, V)]>` implementation of [`FcHashMap`](crate::FcHashMap) and by
+/// [`FcHashMap::try_from_slice_strict()`](crate::FcHashMap::try_from_slice_strict).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FromSliceError {
+    /// The key at this index of the slice didn't fit into the full map.
+    Full { index: usize },
+    /// The key at this index of the slice occurred before (only in strict mode).
+    Duplicate { index: usize },
+}
+
+impl fmt::Display for FromSliceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FromSliceError::Full { index } => {
+                write!(f, "the map is full, the key at index {} doesn't fit", index)
+            }
+            FromSliceError::Duplicate { index } => {
+                write!(f, "the key at index {} is a duplicate", index)
+            }
+        }
+    }
+}
+
+impl Error for FromSliceError {}
+
+/// The error of an insert into a full map, which gives back what was to be inserted.
+///
+/// If the value was not produced yet when the map turned out to be full, `V` is `()`. The pair
+/// given back by [`FcHashMap::insert()`](crate::FcHashMap::insert) converts with
+/// `map_err(CapacityError::from)`, or use
+/// [`FcHashMap::checked_insert()`](crate::FcHashMap::checked_insert).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CapacityError<K, V = ()> {
+    /// The rejected key.
+    pub key: K,
+    /// The rejected value.
+    pub value: V,
+}
+
+impl<K, V> fmt::Display for CapacityError<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the map is full")
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> Error for CapacityError<K, V> {}
+
+impl<K, V> From<(K, V)> for CapacityError<K, V> {
+    fn from((key, value): (K, V)) -> Self {
+        CapacityError { key, value }
+    }
+}
+
+/// The error of [`FcHashMap::extract_to()`](crate::FcHashMap::extract_to) and
+/// [`FcHashMap::merge()`](crate::FcHashMap::merge), if the map to move into got full.
+///
+/// The entries that didn't fit remain in the map they were to be moved from. The methods
+/// describe where to find them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveError {
+    /// The number of entries that were moved.
+    pub moved: usize,
+    /// The number of entries that didn't fit.
+    pub not_moved: usize,
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "the map is full, {} entries were moved and {} didn't fit",
+            self.moved, self.not_moved
+        )
+    }
+}
+
+impl Error for MoveError {}
+
+/// The error of [`FcHashMap::try_extend()`](crate::FcHashMap::try_extend) and
+/// [`FcHashMap::try_from_iter()`](crate::FcHashMap::try_from_iter), if the map got full.
+///
+/// The pairs of the iterator behind the rejected one are not consumed, they can still be taken
+/// from an iterator that was passed by reference.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtendError<K, V> {
+    /// The number of pairs that were inserted before the map got full. This is the position of
+    /// the rejected pair in the iterator.
+    pub inserted: usize,
+    /// The rejected key.
+    pub key: K,
+    /// The rejected value.
+    pub value: V,
+}
+
+impl<K, V> fmt::Display for ExtendError<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the map is full after {} inserted entries", self.inserted)
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> Error for ExtendError<K, V> {}
+
+/// The error of [`FcHashMap::exchange_entries()`](crate::FcHashMap::exchange_entries), if the
+/// entries of one map don't fit into the other one. Both maps are left unchanged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExchangeError {
+    /// The number of entries of the map that doesn't fit.
+    pub len: usize,
+    /// The capacity of the map it doesn't fit into.
+    pub capacity: usize,
+}
+
+impl fmt::Display for ExchangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} entries don't fit into a map with a capacity of {}",
+            self.len, self.capacity
+        )
+    }
+}
+
+impl Error for ExchangeError {}
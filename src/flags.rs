@@ -0,0 +1,189 @@
+use crate::{Entry, FcHashMap};
+use core::borrow::Borrow;
+use hash32::Hash;
+
+/// A map with a set of 8 boolean flags per entry.
+///
+/// The flags are stored together with the value, so "dirty" or "active" markers can't drift out
+/// of sync with the map as a separate set of keys would. A flag is given as bit mask, several
+/// flags can be combined with `|`. New entries start with all flags cleared.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::FcHashMapWithFlags;
+///
+/// const DIRTY: u8 = 1 << 0;
+/// const ACTIVE: u8 = 1 << 1;
+///
+/// let mut map = FcHashMapWithFlags::<u32, u32, 16>::new();
+/// map.insert(1, 10).unwrap();
+/// map.insert(2, 20).unwrap();
+/// map.set_flags(&1, DIRTY | ACTIVE);
+/// map.set_flags(&2, ACTIVE);
+///
+/// assert!(map.has_flags(&1, DIRTY));
+/// assert_eq!(map.count_with_flags(ACTIVE), 2);
+///
+/// let dirty: Vec<_> = map.keys_with_flags(DIRTY).collect();
+/// assert_eq!(dirty, vec![&1]);
+///
+/// map.clear_flags_all(DIRTY);
+/// assert_eq!(map.count_with_flags(DIRTY), 0);
+/// ```
+pub struct FcHashMapWithFlags<K, V, const CAP: usize> {
+    map: FcHashMap<K, (V, u8), CAP>,
+}
+
+impl<K, V, const CAP: usize> FcHashMapWithFlags<K, V, CAP> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        FcHashMapWithFlags {
+            map: FcHashMap::new(),
+        }
+    }
+
+    /// Returns the number of elements the map can hold.
+    pub fn capacity(&self) -> usize {
+        CAP
+    }
+
+    /// Remove all key-value pairs and their flags in the map.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Clears `flags` of the entry with the given key. Returns false if the key is not in the map.
+    pub fn clear_flags<Q>(&mut self, key: &Q, flags: u8) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get_mut(key).map(|(_, f)| *f &= !flags).is_some()
+    }
+
+    /// Clears `flags` of all entries.
+    pub fn clear_flags_all(&mut self, flags: u8) {
+        for (_, (_, f)) in self.map.iter_mut() {
+            *f &= !flags;
+        }
+    }
+
+    /// Returns true if the map contains a value for the specified key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Returns the number of entries that have all of `flags` set.
+    pub fn count_with_flags(&self, flags: u8) -> usize {
+        self.map.values().filter(|(_, f)| f & flags == flags).count()
+    }
+
+    /// Returns the flags of the entry with the given key.
+    pub fn flags<Q>(&self, key: &Q) -> Option<u8>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get(key).map(|(_, f)| *f)
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get(key).map(|(v, _)| v)
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get_mut(key).map(|(v, _)| v)
+    }
+
+    /// Returns true if the entry with the given key has all of `flags` set.
+    pub fn has_flags<Q>(&self, key: &Q, flags: u8) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        matches!(self.flags(key), Some(f) if f & flags == flags)
+    }
+
+    /// Inserts a key-value pair into the map, see [`FcHashMap::insert()`].
+    ///
+    /// A new entry starts with all flags cleared. If the key is already in the map, its value is
+    /// replaced and its flags are kept.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)>
+    where
+        K: Hash + Eq,
+    {
+        match self.map.entry(key) {
+            Ok(Entry::Occupied(mut entry)) => {
+                Ok(Some(core::mem::replace(&mut entry.get_mut().0, value)))
+            }
+            Ok(Entry::Vacant(entry)) => {
+                entry.insert((value, 0));
+                Ok(None)
+            }
+            Err(key) => Err((key, value)),
+        }
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Return an iterator over the keys, values and flags of the map, in their order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V, u8)> {
+        self.map.iter().map(|(k, (v, f))| (k, v, *f))
+    }
+
+    /// Return an iterator over the keys of all entries that have all of `flags` set.
+    pub fn keys_with_flags(&self, flags: u8) -> impl Iterator<Item = &K> {
+        self.map
+            .iter()
+            .filter(move |(_, (_, f))| f & flags == flags)
+            .map(|(k, _)| k)
+    }
+
+    /// Return the number of key-value pairs in the map.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously
+    /// in the map. The flags of the entry are dropped with it.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.remove(key).map(|(v, _)| v)
+    }
+
+    /// Sets `flags` of the entry with the given key. Returns false if the key is not in the map.
+    pub fn set_flags<Q>(&mut self, key: &Q, flags: u8) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get_mut(key).map(|(_, f)| *f |= flags).is_some()
+    }
+}
+
+impl<K, V, const CAP: usize> Default for FcHashMapWithFlags<K, V, CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
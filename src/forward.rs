@@ -0,0 +1,50 @@
+// The methods that the wrapper types around FcHashMap forward unchanged to the map in their field
+// named map. It is used inside an impl block with the generic parameters K, V and CAP of the map.
+// The methods whose behavior or documentation differs, like insert() or iter(), are written out
+// by each wrapper.
+macro_rules! forward_to_map {
+    () => {
+        /// Returns the inner map, for the read only methods of [`FcHashMap`](crate::FcHashMap)
+        /// that this type doesn't provide itself.
+        pub fn as_map(&self) -> &crate::FcHashMap<K, V, CAP> {
+            &self.map
+        }
+
+        /// Returns true if the map contains a value for the specified key.
+        pub fn contains_key<Q>(&self, key: &Q) -> bool
+        where
+            K: core::borrow::Borrow<Q>,
+            Q: ?Sized + hash32::Hash + Eq,
+        {
+            self.map.contains_key(key)
+        }
+
+        /// Returns a reference to the value corresponding to the key.
+        pub fn get<Q>(&self, key: &Q) -> Option<&V>
+        where
+            K: core::borrow::Borrow<Q>,
+            Q: ?Sized + hash32::Hash + Eq,
+        {
+            self.map.get(key)
+        }
+
+        /// Returns a mutable reference to the value corresponding to the key.
+        pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+        where
+            K: core::borrow::Borrow<Q>,
+            Q: ?Sized + hash32::Hash + Eq,
+        {
+            self.map.get_mut(key)
+        }
+
+        /// Returns true if the map contains no elements.
+        pub fn is_empty(&self) -> bool {
+            self.map.is_empty()
+        }
+
+        /// Return the number of key-value pairs in the map.
+        pub fn len(&self) -> usize {
+            self.map.len()
+        }
+    };
+}
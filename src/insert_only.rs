@@ -0,0 +1,91 @@
+use crate::{FcHashMap, Iter, IterMut, Keys, Values, ValuesMut};
+use hash32::Hash;
+
+/// A map whose entries can only be inserted, until the whole map is cleared.
+///
+/// The map has no methods to remove single entries, so code that is handed this map can only
+/// add entries. This fits tables that are filled at boot time and only grow afterwards. It only
+/// restricts the API: a program uses as much code as with an [`FcHashMap`] on which it never
+/// calls a removal method.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::FcInsertOnlyHashMap;
+///
+/// // Register name -> address
+/// let mut registers = FcInsertOnlyHashMap::<&str, u16, 16>::new();
+/// registers.insert("status", 0x00).unwrap();
+/// registers.insert("control", 0x04).unwrap();
+///
+/// assert_eq!(registers.get("control"), Some(&0x04));
+/// assert_eq!(registers.len(), 2);
+///
+/// registers.clear();
+/// assert!(registers.is_empty());
+/// ```
+pub struct FcInsertOnlyHashMap<K, V, const CAP: usize> {
+    map: FcHashMap<K, V, CAP>,
+}
+
+impl<K, V, const CAP: usize> FcInsertOnlyHashMap<K, V, CAP> {
+    /// Creates an empty map.
+    pub const fn new() -> Self {
+        FcInsertOnlyHashMap {
+            map: FcHashMap::new(),
+        }
+    }
+
+    /// Returns the number of elements the map can hold.
+    pub fn capacity(&self) -> usize {
+        CAP
+    }
+
+    forward_to_map!();
+
+    /// Remove all key-value pairs in the map.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Inserts a key-value pair into the map, see [`FcHashMap::insert()`].
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)>
+    where
+        K: Hash + Eq,
+    {
+        self.map.insert(key, value)
+    }
+
+    /// Return an iterator over the key-value pairs of the map, in the order of insertion.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.map.iter()
+    }
+
+    /// Return an iterator over the key-value pairs of the map with mutable references to the
+    /// values, in the order of insertion.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.map.iter_mut()
+    }
+
+    /// Return an iterator over the keys of the map, in the order of insertion.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        self.map.keys()
+    }
+
+    /// Return an iterator over the values of the map, in the order of insertion.
+    pub fn values(&self) -> Values<'_, K, V> {
+        self.map.values()
+    }
+
+    /// Return an iterator over mutable references to the values of the map, in the order of
+    /// insertion.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        self.map.values_mut()
+    }
+}
+
+impl<K, V, const CAP: usize> Default for FcInsertOnlyHashMap<K, V, CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
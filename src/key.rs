@@ -0,0 +1,260 @@
+//! Helpers to build composite keys that hash well.
+//!
+//! The map only uses the lower 15 bits of the 32 bit FNV hash of a key to find its place in the
+//! hash table. Keys whose entropy sits in a few high bits (e.g. a counter shifted into the upper
+//! half of a word) therefore collide much more often than necessary, and the map becomes slow.
+//!
+//! This module offers some small tools against that:
+//!
+//! - [`combine()`] and [`mix32()`] to combine and finalize 32 bit hash values by hand
+//! - [`PackedKey`] to pack several small fields into one key without a `#[derive]`
+//! - [`Mixed`] to wrap an existing key type and spread its hash over all bits
+//! - [`CoreHashAdapter`] and [`Hash32Adapter`] to use keys that implement only one of
+//!   `core::hash::Hash` and `hash32::Hash` where the other is required
+//! - [`ValidateKey`] to keep degenerate keys out of the map
+//!
+//! ## Example
+//!
+//! ```
+//! use fchashmap::FcHashMap;
+//! use fchashmap::key::PackedKey;
+//!
+//! // node id (8 bit), channel (4 bit) and sequence number (16 bit)
+//! let key = PackedKey::new().with(0x2a, 8).with(3, 4).with(0x1234, 16);
+//!
+//! let mut map = FcHashMap::<_, _, 16>::new();
+//! map.insert(key, "sample").unwrap();
+//! assert_eq!(map.get(&key), Some(&"sample"));
+//! ```
+use core::hash as core_hash;
+use hash32::{BuildHasher, BuildHasherDefault, FnvHasher, Hash, Hasher};
+
+/// Combines the hash value `hash` into `seed`.
+///
+/// The result depends on the order of the combined values, so `(a, b)` and `(b, a)` lead to
+/// different hash values.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::key::combine;
+///
+/// let h = combine(combine(0, 1), 2);
+/// assert_ne!(h, combine(combine(0, 2), 1));
+/// ```
+pub const fn combine(seed: u32, hash: u32) -> u32 {
+    seed ^ hash
+        .wrapping_add(0x9e37_79b9)
+        .wrapping_add(seed << 6)
+        .wrapping_add(seed >> 2)
+}
+
+/// Spreads the entropy of `x` over all 32 bits.
+///
+/// This is the finalizer of MurmurHash3. Every input bit affects every output bit, which makes
+/// sure that keys differing only in their high bits end up at different places in the map.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::key::mix32;
+///
+/// // The lower 15 bits, which select the place in the hash table, differ
+/// assert_ne!(mix32(1 << 24) & 0x7fff, mix32(2 << 24) & 0x7fff);
+/// ```
+pub const fn mix32(mut x: u32) -> u32 {
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x85eb_ca6b);
+    x ^= x >> 13;
+    x = x.wrapping_mul(0xc2b2_ae35);
+    x ^= x >> 16;
+    x
+}
+
+// Fold a 64 bit value into 32 bits and mix the result
+const fn mix64(x: u64) -> u32 {
+    mix32((x as u32) ^ mix32((x >> 32) as u32))
+}
+
+/// A composite key made of up to 64 bits of packed bitfields.
+///
+/// Fields are appended with [`with()`](PackedKey::with), the first field ends up in the most
+/// significant position. The `Hash` implementation mixes all bits, so the layout of the fields does
+/// not matter for the distribution in the map.
+///
+/// In debug builds, `with()` asserts that each field fits into its declared width and that the
+/// total width does not exceed 64 bits. Silently truncated fields would produce colliding keys.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct PackedKey {
+    bits: u64,
+    width: u32,
+}
+
+impl PackedKey {
+    /// Creates an empty packed key.
+    pub const fn new() -> Self {
+        PackedKey { bits: 0, width: 0 }
+    }
+
+    /// Appends the field `value` with a width of `width` bits.
+    pub const fn with(self, value: u64, width: u32) -> Self {
+        debug_assert!(width > 0 && self.width + width <= 64);
+        debug_assert!(width == 64 || value >> width == 0);
+        let bits = if width == 64 { value } else { (self.bits << width) | value };
+        PackedKey { bits, width: self.width + width }
+    }
+
+    /// Returns the packed bits of the key.
+    pub const fn raw(&self) -> u64 {
+        self.bits
+    }
+}
+
+impl Hash for PackedKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(&mix64(self.bits).to_le_bytes());
+    }
+}
+
+/// A wrapper that spreads the hash of its key over all bits.
+///
+/// The inner key is hashed on its own and the result is run through [`mix32()`] before it is
+/// passed to the hasher of the map. Use this for key types with poor entropy in their low bits.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::FcHashMap;
+/// use fchashmap::key::Mixed;
+///
+/// let mut map = FcHashMap::<_, _, 16>::new();
+/// map.insert(Mixed(0x0100_0000_u32), "a").unwrap();
+/// map.insert(Mixed(0x0200_0000_u32), "b").unwrap();
+/// assert_eq!(map.get(&Mixed(0x0200_0000_u32)), Some(&"b"));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Mixed<T>(pub T);
+
+impl<T: Hash> Hash for Mixed<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let mut h = BuildHasherDefault::<FnvHasher>::default().build_hasher();
+        self.0.hash(&mut h);
+        state.write(&mix32(h.finish()).to_le_bytes());
+    }
+}
+
+/// A wrapper that makes a key implementing `core::hash::Hash` usable as key of the map.
+///
+/// The bytes the key feeds into a `core::hash::Hasher` are passed on to the 32 bit hasher of the
+/// map. Note that `core::hash::Hash` feeds lengths (e.g. of slices or strings) as `usize`, so
+/// the hash values of such keys differ between 32 and 64 bit targets.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::FcHashMap;
+/// use fchashmap::key::CoreHashAdapter;
+///
+/// #[derive(Debug, Hash, PartialEq, Eq)]
+/// struct NodeId(u16, u8);
+///
+/// let mut map = FcHashMap::<_, _, 16>::new();
+/// map.insert(CoreHashAdapter(NodeId(7, 1)), "node").unwrap();
+/// assert_eq!(map.get(&CoreHashAdapter(NodeId(7, 1))), Some(&"node"));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct CoreHashAdapter<T>(pub T);
+
+impl<T: core_hash::Hash> Hash for CoreHashAdapter<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        core_hash::Hash::hash(&self.0, &mut CoreHasher(state));
+    }
+}
+
+// Makes a hash32::Hasher usable as core::hash::Hasher
+struct CoreHasher<'a, H>(&'a mut H);
+
+impl<'a, H: Hasher> core_hash::Hasher for CoreHasher<'a, H> {
+    fn finish(&self) -> u64 {
+        u64::from(self.0.finish())
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+}
+
+/// A wrapper that makes a key implementing `hash32::Hash` usable where `core::hash::Hash` is
+/// required, e.g. as key of a `std::collections::HashMap` on the host.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::key::{Hash32Adapter, PackedKey};
+/// use std::collections::HashSet;
+///
+/// let mut set = HashSet::new();
+/// set.insert(Hash32Adapter(PackedKey::new().with(3, 8)));
+/// assert!(set.contains(&Hash32Adapter(PackedKey::new().with(3, 8))));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Hash32Adapter<T>(pub T);
+
+impl<T: Hash> core_hash::Hash for Hash32Adapter<T> {
+    fn hash<H: core_hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(&mut Hash32Hasher(state));
+    }
+}
+
+// Makes a core::hash::Hasher usable as hash32::Hasher
+struct Hash32Hasher<'a, H>(&'a mut H);
+
+impl<'a, H: core_hash::Hasher> Hasher for Hash32Hasher<'a, H> {
+    fn finish(&self) -> u32 {
+        self.0.finish() as u32
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        self.0.write(bytes);
+    }
+}
+
+/// Validation of keys before they are inserted into the map.
+///
+/// Implement this trait for key types that have degenerate values, e.g. all-zero device ids or
+/// uninitialized sentinel values read from corrupted packets. In debug builds, or with the
+/// `hardening` feature, such keys are rejected by
+/// [`FcHashMap::insert_validated()`](crate::FcHashMap::insert_validated) instead of silently
+/// occupying a place in the map.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::key::ValidateKey;
+///
+/// #[derive(Debug, PartialEq, Eq)]
+/// struct DeviceId([u8; 4]);
+///
+/// impl hash32::Hash for DeviceId {
+///     fn hash<H: hash32::Hasher>(&self, state: &mut H) {
+///         self.0.hash(state)
+///     }
+/// }
+///
+/// impl ValidateKey for DeviceId {
+///     fn validate(&self) -> bool {
+///         self.0 != [0; 4] && self.0 != [0xff; 4]
+///     }
+/// }
+///
+/// assert!(!DeviceId([0; 4]).validate());
+///
+/// let mut map = fchashmap::FcHashMap::<DeviceId, u8, 16>::new();
+/// assert!(map.insert_validated(DeviceId([1, 2, 3, 4]), 1).is_ok());
+/// // Rejected in debug builds
+/// let _ = map.insert_validated(DeviceId([0; 4]), 2);
+/// ```
+pub trait ValidateKey {
+    /// Returns true if the key may be stored in the map.
+    fn validate(&self) -> bool;
+}
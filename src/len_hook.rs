@@ -0,0 +1,143 @@
+use crate::{FcHashMap, Iter, IterMut};
+use core::borrow::Borrow;
+use hash32::Hash;
+
+/// A crossing of a threshold by the number of entries of a [`FcHashMapWithLenHook`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LenCrossing {
+    /// The threshold that was crossed.
+    pub threshold: usize,
+    /// The number of entries after the operation.
+    pub len: usize,
+    /// True if the number of entries reached the threshold, false if it fell below it.
+    pub rising: bool,
+}
+
+/// A map that calls a hook when its number of entries crosses one of a set of thresholds.
+///
+/// The number of entries reaches a threshold `t` when it grows from below `t` to `t` or more,
+/// and falls below it when it shrinks from `t` or more to below `t`. So a threshold of 1 reports
+/// when the map becomes empty, and a threshold of `CAP` when it becomes full. The hook is called
+/// once for every crossed threshold, in the order of the thresholds, e.g. to switch LEDs or to
+/// send telemetry without polling `len()`.
+///
+/// The hook is called by the operations that change the number of entries, after the map has
+/// been changed. A plain [`FcHashMap`] has no hook and no cost for it.
+///
+/// ## Example
+///
+/// ```
+/// use core::cell::RefCell;
+/// use fchashmap::{FcHashMapWithLenHook, LenCrossing};
+///
+/// let events = RefCell::new(Vec::new());
+/// let hook = |c: LenCrossing| events.borrow_mut().push((c.threshold, c.rising));
+/// let mut map = FcHashMapWithLenHook::<u32, u32, _, 16, 3>::new([1, 12, 16], hook);
+///
+/// for i in 0..12 {
+///     map.insert(i, i).unwrap();
+/// }
+/// map.retain(|k, _| k % 2 == 0);
+/// map.clear();
+/// assert_eq!(*events.borrow(), [(1, true), (12, true), (12, false), (1, false)]);
+/// ```
+pub struct FcHashMapWithLenHook<K, V, F, const CAP: usize, const N: usize>
+where
+    F: FnMut(LenCrossing),
+{
+    map: FcHashMap<K, V, CAP>,
+    thresholds: [usize; N],
+    on_len_change: F,
+}
+
+impl<K, V, F, const CAP: usize, const N: usize> FcHashMapWithLenHook<K, V, F, CAP, N>
+where
+    F: FnMut(LenCrossing),
+{
+    /// Creates an empty map, which calls `on_len_change` whenever its number of entries crosses
+    /// one of the `thresholds`.
+    pub fn new(thresholds: [usize; N], on_len_change: F) -> Self {
+        FcHashMapWithLenHook {
+            map: FcHashMap::new(),
+            thresholds,
+            on_len_change,
+        }
+    }
+
+    /// Returns the number of elements the map can hold.
+    pub fn capacity(&self) -> usize {
+        CAP
+    }
+
+    forward_to_map!();
+
+    /// Remove all key-value pairs in the map.
+    pub fn clear(&mut self) {
+        let len = self.map.len();
+        self.map.clear();
+        self.notify(len);
+    }
+
+    /// Inserts a key-value pair into the map, see [`FcHashMap::insert()`].
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)>
+    where
+        K: Hash + Eq,
+    {
+        let len = self.map.len();
+        let result = self.map.insert(key, value);
+        self.notify(len);
+        result
+    }
+
+    /// Return an iterator over the key-value pairs of the map, in their order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.map.iter()
+    }
+
+    /// Return an iterator over the key-value pairs of the map with mutable references to the
+    /// values, in their order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.map.iter_mut()
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously
+    /// in the map.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let len = self.map.len();
+        let value = self.map.remove(key);
+        self.notify(len);
+        value
+    }
+
+    /// Retains only the elements specified by the predicate, see [`FcHashMap::retain()`].
+    pub fn retain<P>(&mut self, pred: P)
+    where
+        P: FnMut(&K, &mut V) -> bool,
+    {
+        let len = self.map.len();
+        self.map.retain(pred);
+        self.notify(len);
+    }
+
+    // Call the hook for every threshold between the old and the current number of entries
+    fn notify(&mut self, old_len: usize) {
+        let len = self.map.len();
+        if len == old_len {
+            return;
+        }
+        for &threshold in self.thresholds.iter() {
+            let rising = len >= threshold;
+            if rising != (old_len >= threshold) {
+                (self.on_len_change)(LenCrossing {
+                    threshold,
+                    len,
+                    rising,
+                });
+            }
+        }
+    }
+}
@@ -10,11 +10,112 @@
 //! ([1](https://codecapsule.com/2013/11/11/robin-hood-hashing/),
 //! [2](https://codecapsule.com/2013/11/17/robin-hood-hashing-backward-shift-deletion/))
 //! the functionality is explained very nicely.
-#![cfg_attr(not(test), no_std)]
+//!
+//! ## Features
+//!
+//! - `can`: Adds the [`can`] module with key types for CAN and J1939 identifiers and a map that
+//!   is directly indexed by 11 bit identifiers.
+//! - `debug-truncate`: The `Debug` output of a map shows only its first 32 entries, so that
+//!   logging a large map by accident does not stall a slow serial line. See also
+//!   [`FcHashMap::debug_truncated()`].
+//! - `hardening`: Checks the consistency of the hash table in release builds as well, so that
+//!   memory corrupted from outside (e.g. by a buffer overflow) leads to a panic instead of
+//!   wrong lookups. This costs a few comparisons per lookup. Keys are validated by
+//!   [`FcHashMap::insert_validated()`] in release builds as well.
+//! - `key-diagnostics`: In debug builds, `insert()` panics with a hint if the keys are so badly
+//!   distributed over the hash table that the map becomes slow. See the [`key`] module for help.
+//! - `net`: Adds the [`net`] module with key types and maps for network stacks, e.g. neighbor
+//!   caches and flow tables.
+//! - `panic-position`: In debug builds, the panic of `extend()` and `collect()` on a full map
+//!   names the position of the rejected entry in the iterator. The rejected key itself is
+//!   given back by [`FcHashMap::try_extend()`].
+//! - `recorder`: Adds the [`Recorder`](oplog::Recorder), which logs map operations on the
+//!   target for a later replay.
+//! - `simd`: On x86_64, lookups compare the hash values of 8 hash table entries at once with
+//!   SSE2 instructions, as in hashbrown. This speeds up lookups on hosts, e.g. for gateway
+//!   software or simulators sharing code with the target. Other targets keep the scalar search.
+//! - `stable-layout`: Fixes the memory layout of the map, so that persisted images stay readable
+//!   across compiler versions. See [`FcHashMap::LAYOUT_SIGNATURE`].
+//! - `stats`: Counts the full key comparisons of the map. See [`FcHashMap::key_comparisons()`].
+//! - `tags`: Keeps a tag byte for every place of the hash table. A lookup reads the place only
+//!   if its tag matches, so a miss reads one byte per place instead of four. This costs `CAP`
+//!   bytes. With the `simd` feature, lookups use the SIMD search instead.
+//! - `std`: Adds the [`replay`](crate::replay) module, which replays logs of map operations on
+//!   the host.
+#![cfg_attr(not(any(test, feature = "std")), no_std)]
+mod align;
+mod arena;
+mod bounds;
+mod bulk;
+mod cache;
+#[cfg(feature = "can")]
+pub mod can;
+mod capacity;
+mod duplicates;
+mod dyn_map;
+mod entry;
+mod error;
+mod flags;
+#[macro_use]
+mod forward;
+mod insert_only;
+pub mod key;
+mod len_hook;
+mod limited;
+mod macros;
 mod map;
-use map::{Iter, IterMut, Map};
+#[cfg(feature = "net")]
+pub mod net;
+mod num;
+pub mod oplog;
+mod ordered;
+#[cfg(feature = "std")]
+pub mod replay;
+mod set_view;
+mod stable;
+#[cfg(target_has_atomic = "8")]
+mod static_map;
+mod suggestion;
+mod value_table;
+pub use align::{Align16, Align32, Align4, Align64, Align8};
+pub use arena::{KeyArena, KeyHandle};
+pub use bounds::{OpBounds, ProbeStats};
+use arrayvec::ArrayVec;
+pub use bulk::BulkLoader;
+pub use cache::FcLruTtlCache;
+pub use capacity::{capacity_for, recommended_capacity};
+pub use duplicates::Duplicates;
+pub use dyn_map::{DynFixedMap, MapLoad};
+pub use entry::{Entry, OccupiedEntry, VacantEntry};
+pub use error::{
+    CapacityError, CollisionError, ExchangeError, ExtendError, FromSliceError, MoveError,
+    TryInsertError,
+};
+pub use flags::FcHashMapWithFlags;
+pub use insert_only::FcInsertOnlyHashMap;
+pub use len_hook::{FcHashMapWithLenHook, LenCrossing};
+pub use limited::FcHashMapWithLimit;
+use map::{Map, ReindexOnUnwind};
+pub use map::{Chunks, ExtractIf, IntoIter, Iter, IterMut, Keys, PendingFind, Values, ValuesMut};
+pub use num::SaturatingAdd;
+pub use ordered::FcOrderedHashMap;
+pub use set_view::FcHashSetView;
+pub use stable::FcStableHashMap;
+#[cfg(target_has_atomic = "8")]
+pub use static_map::StaticFcHashMap;
+pub use suggestion::Suggestion;
+pub use value_table::{FcKeyIndex, FcValueTable};
 //use std::{fmt::Display};
-use core::{borrow::Borrow, fmt, iter::FromIterator, ops};
+use core::{
+    borrow::Borrow,
+    cell::Cell,
+    cmp::Ordering,
+    convert::TryFrom,
+    fmt,
+    iter::{FromIterator, FusedIterator},
+    mem::{self, MaybeUninit},
+    ops,
+};
 use hash32::Hash;
 
 /// A fixed capacity no_std hashmap.
@@ -25,11 +126,15 @@ use hash32::Hash;
 ///
 /// - The size of the hashmap must be fixed at compile time
 /// - 8 bytes ram are consumed per entry without keys and values
-/// - The maximum capacity is limited to 32768 entries
+/// - The maximum capacity is limited to 32768 entries, larger capacities fail to compile
 /// - The capacity must be chosen as a power of 2
 /// - The hashmap should not be used to its full capacity, otherwise it will become slow.
 ///   10 to 20 percent of the capacity should always be kept free.
 ///
+/// There are no reserved keys. Every bit pattern of a key, including all zeros and all ones, can
+/// be stored. Empty places in the hash table are marked with a bit that is never part of a
+/// calculated hash value, so neither a key nor its hash value serve as a sentinel.
+///
 /// ## Example
 ///
 /// ```
@@ -81,6 +186,57 @@ use hash32::Hash;
 /// about 80%.
 ///
 /// ![Image](https://raw.githubusercontent.com/Simsys/fchashmap/master/benches/cm4_performance/fchashmap.png)
+///
+/// The time of a lookup follows the number of places of the hash table it reads. For keys with
+/// well spread hash values, the tests of this crate ensure these bounds of the mean number, also
+/// after many removals (see [`probe_stats()`](FcHashMap::probe_stats)):
+///
+/// | Load | Key in the map | Missing key |
+/// |------|----------------|-------------|
+/// | 50%  | < 1.8          | < 3         |
+/// | 75%  | < 3            | < 10        |
+/// | 90%  | < 6.5          | < 60        |
+///
+/// For the worst case with any keys, see [`worst_case_ops()`](FcHashMap::worst_case_ops).
+///
+/// ## Interior mutability
+///
+/// Values with interior mutability, like `Cell<T>` or `RefCell<T>`, can be changed through the
+/// shared references that `get()`, `iter()` or `values()` return. This is well defined: the map
+/// moves its entries only in methods that take `&mut self`, so no entry moves while a shared
+/// reference to it exists. Several entries can be borrowed at the same time, e.g. with
+/// `borrow_mut()` of two `RefCell` values. For `Cell` values, [`get_cell()`](FcHashMap::get_cell),
+/// [`set_cell()`](FcHashMap::set_cell) and [`update_cell()`](FcHashMap::update_cell) do this
+/// with a single call.
+///
+/// The following rules apply:
+///
+/// - Keys must never be changed through interior mutability. A key whose hash value or equality
+///   changes can't be found anymore, and the map will behave wrongly (but not unsafely).
+/// - A map with `Cell` or `RefCell` values is not `Sync`. To share it between the main loop and
+///   an interrupt handler on a single core, put it into a mutex that masks the interrupts while
+///   the map is accessed, e.g. `critical_section::Mutex`. Within the critical section, the values
+///   can be changed through a shared reference.
+/// - A `RefCell` value panics if it is borrowed mutably twice, e.g. by code that is reentered
+///   while it holds a borrow. `Cell` values never panic.
+///
+/// ## Stack usage
+///
+/// No operation of the map is recursive. All searches and shifts in the hash table are plain
+/// loops, so the stack usage of an operation does not grow with the capacity or the number of
+/// entries. Besides a few indices, `insert()` holds one entry of the hash table (4 bytes) while
+/// it shifts the following entries, and the key-value pair it was given.
+///
+/// The tests run inserts, removals, lookups, `retain()`, `extract_if()` and `clone_from()` of a
+/// map with 4096 entries on a thread with a 16 KiB stack, the smallest thread stack on Linux. The
+/// actual stack usage depends on the target, the compiler and the key and value types, so a
+/// budget of a few KiB has to be checked on the target itself.
+///
+/// The map itself lives where it is placed. Functions that return a map by value, like `new()`,
+/// `clone()`, `collect()` or `map_values()`, may place a temporary copy of the whole map on the
+/// stack. On tasks with a small stack, create large maps in a `static` or on the stack of a task
+/// that can hold them. `clone_from()` copies a map into an existing one in place.
+#[cfg_attr(feature = "stable-layout", repr(transparent))]
 pub struct FcHashMap<K, V, const CAP: usize> {
     map: Map<K, V, CAP>,
 }
@@ -89,6 +245,30 @@ impl<K, V, const CAP: usize> FcHashMap<K, V, CAP>
 {
     //    pub fn show(&self) { self.map.show() }
 
+    /// Signature of the memory layout of this map type.
+    ///
+    /// With the `stable-layout` feature, the memory layout of the map is fixed and does not depend
+    /// on the compiler version. The signature covers the layout version of this crate, the sizes
+    /// and alignments of `K` and `V` and the capacity. Store it together with a persisted image of
+    /// the map and compare it before the image is used again.
+    ///
+    /// The entries are stored in an `ArrayVec` of arrayvec 0.7, which is laid out as its length
+    /// followed by the array of entries. arrayvec does not promise to keep this layout. A release
+    /// whose `ArrayVec` differs in size or alignment fails to compile, a reordering of its fields
+    /// of the same size is not detected. Keep the arrayvec release of the `Cargo.lock` that wrote
+    /// the images.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// type Table = FcHashMap<u32, u16, 64>;
+    /// assert_ne!(Table::LAYOUT_SIGNATURE, FcHashMap::<u32, u32, 64>::LAYOUT_SIGNATURE);
+    /// ```
+    #[cfg(feature = "stable-layout")]
+    pub const LAYOUT_SIGNATURE: u32 = Map::<K, V, CAP>::LAYOUT_SIGNATURE;
+
     /// Creates an empty HashMap.
     ///
     /// The hash map is initially created with no elements inside. The maximum capacity must be set
@@ -100,7 +280,14 @@ impl<K, V, const CAP: usize> FcHashMap<K, V, CAP>
     /// use fchashmap::FcHashMap;
     /// let mut map: FcHashMap<u32, i32, 16> = FcHashMap::new();
     /// ```
-    pub fn new() -> Self {
+    ///
+    /// The capacity must be a power of 2 and at most 32768, otherwise the build fails:
+    ///
+    /// ```compile_fail
+    /// use fchashmap::FcHashMap;
+    /// let mut map: FcHashMap<u32, i32, 100> = FcHashMap::new();
+    /// ```
+    pub const fn new() -> Self {
         FcHashMap { map: Map::new() }
     }
 
@@ -109,6 +296,101 @@ impl<K, V, const CAP: usize> FcHashMap<K, V, CAP>
         CAP
     }
 
+    /// Returns a view of the keys of the map as a set, see [`FcHashSetView`].
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<u32, &str, 16>::new();
+    /// map.insert(1, "a").unwrap();
+    /// map.insert(2, "b").unwrap();
+    ///
+    /// let keys = map.as_set();
+    /// assert!(keys.contains(&2));
+    /// assert_eq!(keys.len(), 2);
+    /// ```
+    pub fn as_set(&self) -> FcHashSetView<'_, K, V, CAP> {
+        FcHashSetView::new(self)
+    }
+
+    /// Starts a lookup in two phases by calculating the hash value of the key.
+    ///
+    /// The returned [`PendingFind`] is passed to [`complete_find()`](FcHashMap::complete_find),
+    /// which searches the hash table. In between, the caller can do other work, e.g. hash the
+    /// next key of a batch or wait for a DMA transfer, so that hashing and searching of
+    /// successive keys overlap. The pending lookup does not borrow the map, so the map may even
+    /// be changed in between.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<u32, &str, 16>::new();
+    /// map.insert(1, "a").unwrap();
+    /// map.insert(2, "b").unwrap();
+    ///
+    /// let keys = [1, 2, 3];
+    /// let mut pending = map.begin_find(&keys[0]);
+    /// let mut found = Vec::new();
+    /// for key in keys[1..].iter() {
+    ///     let next = map.begin_find(key);
+    ///     found.push(map.complete_find(pending));
+    ///     pending = next;
+    /// }
+    /// found.push(map.complete_find(pending));
+    ///
+    /// assert_eq!(found, vec![Some(&"a"), Some(&"b"), None]);
+    /// ```
+    pub fn begin_find<'q, Q>(&self, key: &'q Q) -> PendingFind<'q, Q>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.begin_find(key)
+    }
+
+    /// Inserts a key-value pair into the map like [`insert()`](FcHashMap::insert), but returns a
+    /// [`CapacityError`] with the rejected pair if the map is full.
+    ///
+    /// `CapacityError` implements `core::error::Error`, so it can be propagated with `?` into
+    /// an error type that converts from it.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::{CapacityError, FcHashMap};
+    ///
+    /// #[derive(Debug)]
+    /// enum ConfigError {
+    ///     TooManyEntries(u8),
+    /// }
+    ///
+    /// impl From<CapacityError<u8, u16>> for ConfigError {
+    ///     fn from(err: CapacityError<u8, u16>) -> Self {
+    ///         ConfigError::TooManyEntries(err.key)
+    ///     }
+    /// }
+    ///
+    /// fn load(map: &mut FcHashMap<u8, u16, 2>) -> Result<(), ConfigError> {
+    ///     for id in 0..3 {
+    ///         map.checked_insert(id, 100)?;
+    ///     }
+    ///     Ok(())
+    /// }
+    ///
+    /// let mut map = FcHashMap::new();
+    /// assert!(matches!(load(&mut map), Err(ConfigError::TooManyEntries(2))));
+    /// ```
+    pub fn checked_insert(&mut self, key: K, value: V) -> Result<Option<V>, CapacityError<K, V>>
+    where
+        K: Hash + Eq,
+    {
+        self.insert(key, value).map_err(CapacityError::from)
+    }
+
     /// Remove all key-value pairs in the map.
     ///
     /// ## Example
@@ -126,6 +408,18 @@ impl<K, V, const CAP: usize> FcHashMap<K, V, CAP>
         self.map.clear();
     }
 
+    /// Finishes a lookup started with [`begin_find()`](FcHashMap::begin_find) and returns a
+    /// reference to the value corresponding to the key.
+    pub fn complete_find<Q>(&self, pending: PendingFind<'_, Q>) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map
+            .complete_find(pending)
+            .map(|(_, b_idx)| &self.map.buckets[b_idx].value)
+    }
+
     /// Returns true if the map contains a value for the specified key.
     ///
     /// The key may be any borrowed form of the map's key type, but 'Hash` and `Eq` on the borrowed
@@ -150,10 +444,9 @@ impl<K, V, const CAP: usize> FcHashMap<K, V, CAP>
         self.map.find(key).is_some()
     }
 
-    /// Returns a reference to the value corresponding to the key.
+    /// Returns true if the map contains at least one entry with the value.
     ///
-    /// The key may be any borrowed form of the map's key type, but `Hash` and `Eq` on the borrowed
-    /// form must match those for the key type.
+    /// The map is indexed by its keys, so this is a linear scan over all entries.
     ///
     /// ## Example
     ///
@@ -161,136 +454,217 @@ impl<K, V, const CAP: usize> FcHashMap<K, V, CAP>
     /// use fchashmap::FcHashMap;
     ///
     /// let mut map = FcHashMap::<_, _, 16>::new();
-    /// map.insert(1, "a").unwrap();
-    ///
-    /// assert_eq!(map.get(&1), Some(&"a"));
-    /// assert_eq!(map.get(&2), None);
+    /// map.insert("a", 1).unwrap();
+    /// assert!(map.contains_value(&1));
+    /// assert!(!map.contains_value(&2));
     /// ```
-    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    pub fn contains_value(&self, value: &V) -> bool
     where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash + Eq,
+        V: PartialEq,
     {
-        self.map.get(key)
+        self.values().any(|v| v == value)
     }
 
-    /// Returns a mutable reference to the value corresponding to the key.
+    /// Returns a wrapper whose `Debug` output shows at most `max` entries of the map.
     ///
-    /// The key may be any borrowed form of the map's key type, but `Hash` and `Eq` on the borrowed
-    /// form *must* match those for the key type.
+    /// The omitted entries are summarized in a last entry `..: <n> more`. This limits the time
+    /// it takes to log a large map, e.g. over a slow serial line.
     ///
     /// ## Example
     ///
     /// ```
     /// use fchashmap::FcHashMap;
     ///
-    /// let mut map = FcHashMap::<_, _, 8>::new();
-    /// map.insert(1, "a").unwrap();
-    /// if let Some(x) = map.get_mut(&1) {
-    ///     *x = "b";
-    /// }
-    /// assert_eq!(map.get(&1), Some(&"b"));
+    /// let map: FcHashMap<u32, u32, 1024> = (0..1000).map(|i| (i, i * 2)).collect();
+    /// assert_eq!(
+    ///     format!("{:?}", map.debug_truncated(3)),
+    ///     "{0: 0, 1: 2, 2: 4, ..: 997 more}"
+    /// );
     /// ```
-    pub fn get_mut<'v, Q>(&'v mut self, key: &Q) -> Option<&'v mut V>
+    pub fn debug_truncated(&self, max: usize) -> impl fmt::Debug + '_
     where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash + Eq,
+        K: fmt::Debug,
+        V: fmt::Debug,
     {
-        self.map.get_mut(key)
+        DebugEntries {
+            map: self,
+            limit: max,
+        }
     }
 
-    /// Inserts a key-value pair into the map.
+    /// Marks the entry with the given key for removal, which is done later by
+    /// [`flush_removals()`](FcHashMap::flush_removals).
     ///
-    /// If an equivalent key already exists in the map: the key remains and retains in its place in
-    /// the order, its corresponding value is updated with `value` and the older value is returned
-    /// inside `Some(_)`.
+    /// Removing an entry moves another entry into its place. Deferring the removal allows to
+    /// decide about removals while walking through the map by index or over several passes (e.g.
+    /// GC style sweeps), and to remove all marked entries at once afterwards. Marked entries stay
+    /// fully accessible until they are flushed. The mark is kept in the entry itself, so there is
+    /// no limit on the number of deferred removals.
     ///
-    /// If no equivalent key existed in the map: the new key-value pair is inserted, and `None`
-    /// is returned.
+    /// Returns false if the key is not in the map.
     ///
     /// ## Example
     ///
     /// ```
     /// use fchashmap::FcHashMap;
     ///
-    /// let mut map = FcHashMap::<_, _, 8>::new();
-    /// assert_eq!(map.insert(37, "a"), Ok(None));
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// for i in 0..6 {
+    ///     map.insert(i, i * 10).unwrap();
+    /// }
     ///
-    /// map.insert(37, "b");
-    /// assert_eq!(map.insert(37, "c"), Ok(Some("b")));
-    /// assert_eq!(map.get(&37), Some(&"c"));
+    /// let stale: Vec<_> = map.iter().filter(|(_, v)| **v >= 30).map(|(k, _)| *k).collect();
+    /// for key in stale.iter() {
+    ///     map.defer_remove(key);
+    /// }
+    /// assert_eq!(map.len(), 6);
+    ///
+    /// assert_eq!(map.flush_removals(), 3);
+    /// assert_eq!(map.len(), 3);
+    /// assert_eq!(map.get(&4), None);
     /// ```
-    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)>
+    pub fn defer_remove<Q>(&mut self, key: &Q) -> bool
     where
-        K: Hash + PartialEq,
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
     {
-        self.map.insert(key, value)
+        match self.map.find(key) {
+            Some((_, b_idx)) => {
+                self.map.mark(b_idx);
+                true
+            }
+            None => false,
+        }
     }
 
-    /// Returns true if the map contains no elements.
+    /// Removes all entries and hands each key and value to `f`, e.g. to release the hardware
+    /// resources held by the values. Afterwards the map is empty.
+    ///
+    /// The entries are handed over in the order of the map, as returned by
+    /// [`iter()`](FcHashMap::iter). In contrast to the drop glue of the values, `f` can use
+    /// anything borrowed from outside, like a peripheral singleton. If `f` panics, the remaining
+    /// entries are dropped and the map is left empty.
     ///
     /// ## Example
     ///
     /// ```
     /// use fchashmap::FcHashMap;
     ///
-    /// let mut map = FcHashMap::<_, _, 16>::new();
-    /// assert_eq!(map.is_empty(), true);
+    /// struct Dma { free: Vec<u8> }
     ///
-    /// map.insert(1, "a");
-    /// assert_eq!(map.is_empty(), false);
+    /// // Stream id -> DMA channel
+    /// let mut dma = Dma { free: Vec::new() };
+    /// let mut streams = FcHashMap::<u32, u8, 8>::new();
+    /// streams.insert(10, 3).unwrap();
+    /// streams.insert(11, 5).unwrap();
+    ///
+    /// streams.deinit(|_, channel| dma.free.push(channel));
+    /// assert!(streams.is_empty());
+    /// assert_eq!(dma.free, vec![3, 5]);
     /// ```
-    pub fn is_empty(&self) -> bool {
-        self.map.buckets.len() == 0
+    pub fn deinit<F>(&mut self, f: F)
+    where
+        F: FnMut(K, V),
+    {
+        self.map.deinit(f);
     }
 
-    /// Return an iterator over the key-value pairs of the map, in their order.
+    /// Moves the entries of the map into `out`, sorted with the comparison function `cmp`, and
+    /// returns the number of moved entries.
+    ///
+    /// The entries are sorted in place, no further memory is needed. The first returned number of
+    /// elements of `out` are initialized afterwards and the caller takes over their ownership. If
+    /// `out` is shorter than the map, only the smallest entries are moved and the others remain
+    /// in the map. The remaining entries are reordered, they are left in the order of their
+    /// places in the hash table.
+    ///
+    /// If `cmp` panics, the entries stay in the map, in an unspecified order.
     ///
     /// ## Example
     ///
     /// ```
+    /// use core::mem::MaybeUninit;
     /// use fchashmap::FcHashMap;
     ///
-    /// let mut map = FcHashMap::<_, _, 16>::new();
-    /// map.insert("a", 1).unwrap();
-    /// map.insert("b", 2).unwrap();
-    /// map.insert("c", 3).unwrap();
+    /// let mut samples = FcHashMap::<u32, i16, 16>::new();
+    /// for (time, value) in [(30, -4), (10, 7), (20, 1)].iter() {
+    ///     samples.insert(*time, *value).unwrap();
+    /// }
     ///
-    /// let v: Vec<_> = map.iter().collect();
-    /// assert_eq!(v, vec![(&"a", &1), (&"b", &2), (&"c", &3)]);
+    /// let mut out = [MaybeUninit::<(u32, i16)>::uninit(); 8];
+    /// let n = samples.drain_sorted_into(&mut out, |a, b| a.0.cmp(b.0));
+    ///
+    /// // unsafe is ok, the first n elements have been initialized
+    /// let dump: Vec<_> = out[..n].iter().map(|e| unsafe { e.assume_init() }).collect();
+    /// assert_eq!(dump, vec![(10, 7), (20, 1), (30, -4)]);
+    /// assert!(samples.is_empty());
     /// ```
-    pub fn iter(&self) -> Iter<'_, K, V> {
-        Iter {
-            iter: self.map.buckets.iter(),
+    pub fn drain_sorted_into<F>(&mut self, out: &mut [MaybeUninit<(K, V)>], mut cmp: F) -> usize
+    where
+        F: FnMut((&K, &V), (&K, &V)) -> Ordering,
+    {
+        let guard = ReindexOnUnwind { map: &mut self.map };
+        guard
+            .map
+            .buckets
+            .sort_unstable_by(|a, b| cmp((&a.key, &a.value), (&b.key, &b.value)));
+        mem::forget(guard);
+        let n = self.len().min(out.len());
+        for (slot, bucket) in out.iter_mut().zip(self.map.buckets.drain(..n)) {
+            slot.write((bucket.key, bucket.value));
         }
+        self.map.build_sorted_hash_table();
+        n
     }
 
-    /// Return an iterator over the key-value pairs of the map, in their order.
+    /// Returns the entry of the given key, to look up, insert or remove its value with a single
+    /// search in the map.
+    ///
+    /// If the key is not in the map and the map is full, there is no room for a vacant entry and
+    /// the key is given back as error.
     ///
     /// ## Example
     ///
     /// ```
-    /// use fchashmap::FcHashMap;
-    ///
-    /// let mut map = FcHashMap::<_, _, 16>::new();
-    /// map.insert("a", 1).unwrap();
-    /// map.insert("b", 2).unwrap();
-    /// map.insert("c", 3).unwrap();
+    /// use fchashmap::{Entry, FcHashMap};
     ///
-    /// for (_, val) in map.iter_mut() {
-    ///     *val = 23;
+    /// let mut counters = FcHashMap::<&str, u32, 2>::new();
+    /// for word in ["a", "b", "a"].iter() {
+    ///     *counters.entry(*word).unwrap().or_insert(0) += 1;
     /// }
+    /// assert_eq!(counters.get(&"a"), Some(&2));
     ///
-    /// let v: Vec<_> = map.iter().collect();
-    /// assert_eq!(v, vec![(&"a", &23), (&"b", &23), (&"c", &23)]);
+    /// // The map is full, only present keys have an entry
+    /// assert!(counters.entry("c").is_err());
+    /// counters.entry("b").unwrap().and_modify(|v| *v += 10);
+    /// assert_eq!(counters.get(&"b"), Some(&11));
+    ///
+    /// if let Ok(Entry::Occupied(entry)) = counters.entry("a") {
+    ///     assert_eq!(entry.remove(), 2);
+    /// }
+    /// assert_eq!(counters.len(), 1);
     /// ```
-    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
-        IterMut {
-            iter: self.map.buckets.iter_mut(),
+    pub fn entry(&mut self, key: K) -> Result<Entry<'_, K, V, CAP>, K>
+    where
+        K: Hash + Eq,
+    {
+        match self.map.find_or_hash(&key) {
+            Ok((h_idx, b_idx)) => Ok(Entry::Occupied(OccupiedEntry::new(
+                &mut self.map,
+                h_idx,
+                b_idx,
+            ))),
+            Err(_) if self.map.buckets.is_full() => Err(key),
+            Err(hash) => Ok(Entry::Vacant(VacantEntry::new(&mut self.map, key, hash))),
         }
     }
 
-    /// Return an iterator over the keys of the map, in their order.
+    /// Return an iterator over the key-value pairs of the map, in their order.
+    ///
+    /// This is the same as [`iter()`](FcHashMap::iter). The order of the map is the order in which
+    /// the entries are stored. It is guaranteed that `entries()`, `iter()`, `keys()` and
+    /// `values()` traverse the map in the same order as long as the map is not modified in
+    /// between, so the keys and values can be zipped index for index.
     ///
     /// ## Example
     ///
@@ -300,134 +674,2224 @@ impl<K, V, const CAP: usize> FcHashMap<K, V, CAP>
     /// let mut map = FcHashMap::<_, _, 16>::new();
     /// map.insert("a", 1).unwrap();
     /// map.insert("b", 2).unwrap();
-    /// map.insert("c", 3).unwrap();
     ///
-    /// let v: Vec<_> = map.keys().collect();
-    /// assert_eq!(v, vec![&"a", &"b", &"c"]);
+    /// let zipped: Vec<_> = map.keys().zip(map.values()).collect();
+    /// let entries: Vec<_> = map.entries().collect();
+    /// assert_eq!(zipped, entries);
     /// ```
-    pub fn keys(&self) -> impl Iterator<Item = &K> {
-        self.map.buckets.iter().map(|bucket| &bucket.key)
+    pub fn entries(&self) -> Iter<'_, K, V> {
+        self.iter()
     }
 
-    /// Return the number of key-value pairs in the map.
+    /// Exchanges the entries of this map with the entries of the map `other`, which may have a
+    /// different capacity.
+    ///
+    /// The entries are exchanged in place, without a buffer for the entries of one of the maps,
+    /// and keep their order. No key is hashed again. If the entries of one map don't fit into
+    /// the other one, an [`ExchangeError`] is returned and both maps are left unchanged.
     ///
     /// ## Example
     ///
     /// ```
     /// use fchashmap::FcHashMap;
     ///
-    /// let mut map = FcHashMap::<_, _, 16>::new();
-    /// assert_eq!(map.len(), 0);
+    /// let mut hot = FcHashMap::<u32, u32, 4>::new();
+    /// let mut cold = FcHashMap::<u32, u32, 64>::new();
+    /// hot.insert(1, 10).unwrap();
+    /// cold.insert(2, 20).unwrap();
+    /// cold.insert(3, 30).unwrap();
     ///
-    /// map.insert(1, "a").unwrap();
-    /// assert_eq!(map.len(), 1);
+    /// hot.exchange_entries(&mut cold).unwrap();
+    /// assert_eq!(hot.get(&3), Some(&30));
+    /// assert_eq!(cold.get(&1), Some(&10));
+    /// assert_eq!((hot.len(), cold.len()), (2, 1));
+    ///
+    /// for i in 10..20 {
+    ///     cold.insert(i, i).unwrap();
+    /// }
+    /// let err = hot.exchange_entries(&mut cold).unwrap_err();
+    /// assert_eq!((err.len, err.capacity), (11, 4));
     /// ```
-    pub fn len(&self) -> usize {
-        self.map.buckets.len()
+    pub fn exchange_entries<const CAP2: usize>(
+        &mut self,
+        other: &mut FcHashMap<K, V, CAP2>,
+    ) -> Result<(), ExchangeError> {
+        if self.len() > CAP2 {
+            return Err(ExchangeError {
+                len: self.len(),
+                capacity: CAP2,
+            });
+        }
+        if other.len() > CAP {
+            return Err(ExchangeError {
+                len: other.len(),
+                capacity: CAP,
+            });
+        }
+        self.map.exchange(&mut other.map);
+        Ok(())
     }
 
-    /// Removes a key from the map, returning the value at the key if the key was previously
-    /// in the map.
+    /// Returns an iterator that removes and yields every entry for which `pred` returns true.
     ///
-    /// The key may be any borrowed form of the map’s key type, but Hash and Eq on the borrowed
-    /// form must match those for the key type.
+    /// The entries are visited lazily, from the last to the first entry of the map. Entries that
+    /// are not yet visited when the iterator is dropped stay in the map, as do all entries for
+    /// which `pred` returns false. `pred` may change the values of the entries.
     ///
     /// ## Example
     ///
     /// ```
     /// use fchashmap::FcHashMap;
     ///
-    /// let mut map = FcHashMap::<_, _, 16>::new();
-    /// map.insert(1, "a");
-    /// assert_eq!(map.remove(&1), Some("a"));
-    /// assert_eq!(map.remove(&1), None);
+    /// // Session id -> time of last activity
+    /// let mut sessions: FcHashMap<u32, u32, 16> = (0..8).map(|i| (i, i * 100)).collect();
+    ///
+    /// let now = 1000;
+    /// let mut expired: Vec<_> = sessions.extract_if(|_, &mut t| now - t > 500).collect();
+    /// expired.sort();
+    ///
+    /// assert_eq!(expired, vec![(0, 0), (1, 100), (2, 200), (3, 300), (4, 400)]);
+    /// assert_eq!(sessions.len(), 3);
     /// ```
-    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    pub fn extract_if<F>(&mut self, pred: F) -> ExtractIf<'_, K, V, F, CAP>
     where
-        K: Borrow<Q>,
-        Q: ?Sized + Hash + Eq,
+        F: FnMut(&K, &mut V) -> bool,
     {
-        self.map.remove(key)
+        ExtractIf::new(&mut self.map, pred)
     }
 
-    /// Return an iterator over the values of the map, in their order.
+    /// Moves all entries for which `pred` returns true into the map `other`, which may have a
+    /// different capacity. Returns the number of moved entries.
+    ///
+    /// `pred` is called exactly once for every entry and may change its value. If a key is
+    /// already in `other`, its value is replaced. If `other` gets full, the entries that don't
+    /// fit remain in this map and a [`MoveError`] counts them. They are placed behind all other
+    /// entries, so they are the last `not_moved` entries of [`iter()`](FcHashMap::iter).
     ///
     /// ## Example
     ///
     /// ```
     /// use fchashmap::FcHashMap;
     ///
-    /// let mut map = FcHashMap::<_, _, 16>::new();
-    /// map.insert("a", 1).unwrap();
-    /// map.insert("b", 2).unwrap();
-    /// map.insert("c", 3).unwrap();
+    /// // Session id -> active
+    /// let mut sessions: FcHashMap<u32, bool, 16> = (0..10).map(|i| (i, i % 3 == 0)).collect();
+    /// let mut hot = FcHashMap::<u32, bool, 4>::new();
     ///
-    /// let v: Vec<_> = map.values().collect();
-    /// assert_eq!(v, vec![&1, &2, &3]);
+    /// assert_eq!(sessions.extract_to(&mut hot, |_, active| *active), Ok(4));
+    /// assert_eq!((sessions.len(), hot.len()), (6, 4));
+    ///
+    /// let err = sessions.extract_to(&mut hot, |id, _| id % 2 == 0).unwrap_err();
+    /// assert_eq!((err.moved, err.not_moved), (0, 3));
+    ///
+    /// let mut parked: Vec<_> = sessions.keys().rev().take(err.not_moved).collect();
+    /// parked.sort();
+    /// assert_eq!(parked, vec![&2, &4, &8]);
     /// ```
-    pub fn values(&self) -> impl Iterator<Item = &V> {
-        self.map.buckets.iter().map(|bucket| &bucket.value)
+    pub fn extract_to<F, const CAP2: usize>(
+        &mut self,
+        other: &mut FcHashMap<K, V, CAP2>,
+        mut pred: F,
+    ) -> Result<usize, MoveError>
+    where
+        K: Hash + Eq,
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let mut moved = 0;
+        let mut not_moved = 0;
+        // The entries that don't fit are gathered at the back, behind end. Every matching entry
+        // is first swapped to the place before end, which holds a visited entry or itself.
+        let mut end = self.len();
+        self.map.visit_rev(|map, b_idx| {
+            let bucket = &mut map.buckets[b_idx];
+            if !pred(&bucket.key, &mut bucket.value) {
+                return;
+            }
+            let fits = other.len() < CAP2 || other.contains_key(&bucket.key);
+            end -= 1;
+            map.swap_buckets(b_idx, end);
+            if fits {
+                // The last entry that didn't fit takes the place of the removed one
+                let h_idx = map.h_idx_of(end);
+                let (key, value) = map.remove_found(h_idx, end);
+                let inserted = other.insert_and_get(key, value).is_ok();
+                debug_assert!(inserted);
+                moved += 1;
+            } else {
+                not_moved += 1;
+            }
+        });
+        if not_moved == 0 {
+            Ok(moved)
+        } else {
+            Err(MoveError { moved, not_moved })
+        }
     }
 
-    /// Return an iterator over mutable references to the the values of the map, in their order.
+    /// Returns a copy of the map, made with raw copies of its memory.
+    ///
+    /// With `Copy` keys and values, nothing has to be done per entry, so this is faster than
+    /// `clone()` for large maps, e.g. for snapshots on a hot path. To reuse the memory of an
+    /// existing copy, see `clone_from()`.
     ///
     /// ## Example
     ///
     /// ```
     /// use fchashmap::FcHashMap;
     ///
-    /// let mut map = FcHashMap::<_, _, 16>::new();
-    /// map.insert("a", 1).unwrap();
-    /// map.insert("b", 2).unwrap();
-    /// map.insert("c", 3).unwrap();
-    ///
-    /// for val in map.values_mut() {
-    ///     *val += 10;
+    /// let mut counters = FcHashMap::<u32, u32, 1024>::new();
+    /// for i in 0..800 {
+    ///     counters.insert(i, i * 2).unwrap();
     /// }
     ///
-    /// let v: Vec<_> = map.values().collect();
-    /// assert_eq!(v, vec![&11, &12, &13]);
+    /// let snapshot = counters.fast_clone();
+    /// counters.insert(5, 0).unwrap();
+    /// assert_eq!(snapshot.get(&5), Some(&10));
+    /// assert_eq!(snapshot.len(), 800);
     /// ```
-    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut V> {
-        self.map.buckets.iter_mut().map(|bucket| &mut bucket.value)
-    }
-}
-
-// Implement Clone trait
-impl<K, V, const CAP: usize> Clone for FcHashMap<K, V, CAP>
-where
-    K: Eq + Hash + Clone,
-    V: Clone,
-{
-    fn clone(&self) -> Self {
-        Self {
-            map: self.map.clone(),
+    pub fn fast_clone(&self) -> Self
+    where
+        K: Copy,
+        V: Copy,
+    {
+        FcHashMap {
+            map: self.map.fast_clone(),
         }
     }
-}
 
-// Enable possibility to extract debug informations
-impl<K, V, const CAP: usize> fmt::Debug for FcHashMap<K, V, CAP>
-where
-    K: Eq + Hash + fmt::Debug,
-    V: fmt::Debug,
-{
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_map().entries(self.iter()).finish()
+    /// Removes all entries marked by [`defer_remove()`](FcHashMap::defer_remove) and returns
+    /// their number.
+    pub fn flush_removals(&mut self) -> usize {
+        self.map.remove_marked()
     }
-}
 
-// Extend map with data of another map, consuming input
-impl<K, V, const CAP: usize> Extend<(K, V)> for FcHashMap<K, V, CAP>
-where
-    K: Eq + Hash,
-{
+    /// Folds every value of the map into an accumulator, in the order of the map.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 3).unwrap();
+    /// map.insert("b", 5).unwrap();
+    ///
+    /// let max = map.fold_values(0, |acc, v| if *v > acc { *v } else { acc });
+    /// assert_eq!(max, 5);
+    /// ```
+    pub fn fold_values<B, F>(&self, init: B, mut f: F) -> B
+    where
+        F: FnMut(B, &V) -> B,
+    {
+        let mut acc = init;
+        for bucket in self.map.buckets.iter() {
+            acc = f(acc, &bucket.value);
+        }
+        acc
+    }
+
+    /// Creates a map from an iterator, merging the values of duplicate keys with `f`.
+    ///
+    /// For every key that is already in the map, `f` is called with the value in the map and the
+    /// new value and combines them in place. Every key keeps the position of its first
+    /// occurrence. Like `collect()`, this panics if there are more distinct keys than the map can
+    /// hold.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let samples = [(1, 10), (2, 5), (1, 7), (2, 1), (1, 3)];
+    /// let totals =
+    ///     FcHashMap::<u8, u32, 8>::from_iter_reduce(samples.iter().copied(), |a, b| *a += b);
+    ///
+    /// assert_eq!(totals.get(&1), Some(&20));
+    /// assert_eq!(totals.get(&2), Some(&6));
+    /// assert!(totals.keys().eq([1, 2].iter()));
+    /// ```
+    pub fn from_iter_reduce<I, F>(iter: I, mut f: F) -> Self
+    where
+        K: Hash + Eq,
+        I: IntoIterator<Item = (K, V)>,
+        F: FnMut(&mut V, V),
+    {
+        let mut map = FcHashMap::new();
+        for (position, (key, value)) in iter.into_iter().enumerate() {
+            if let Some(existing) = map.get_mut(&key) {
+                f(existing, value);
+            } else if map.insert(key, value).is_err() {
+                panic_full(position, CAP);
+            }
+        }
+        map
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but `Hash` and `Eq` on the borrowed
+    /// form must match those for the key type.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert(1, "a").unwrap();
+    ///
+    /// assert_eq!(map.get(&1), Some(&"a"));
+    /// assert_eq!(map.get(&2), None);
+    /// ```
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get(key)
+    }
+
+    /// Returns mutable references to the values of several keys at once.
+    ///
+    /// The result has one element per key, which is `None` if the key is not in the map.
+    ///
+    /// Panics if a key that is in the map is given more than once.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut balances = FcHashMap::<&str, u32, 16>::new();
+    /// balances.insert("src", 100).unwrap();
+    /// balances.insert("dst", 20).unwrap();
+    ///
+    /// if let [Some(src), Some(dst), None] = balances.get_disjoint_mut([&"src", &"dst", &"x"]) {
+    ///     *src -= 30;
+    ///     *dst += 30;
+    /// }
+    /// assert_eq!(balances.get(&"src"), Some(&70));
+    /// assert_eq!(balances.get(&"dst"), Some(&50));
+    /// ```
+    pub fn get_disjoint_mut<Q, const N: usize>(&mut self, keys: [&Q; N]) -> [Option<&mut V>; N]
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let b_idxs = keys.map(|key| self.map.find(key).map(|(_, b_idx)| b_idx));
+        for (i, b_idx) in b_idxs.iter().enumerate() {
+            assert!(
+                b_idx.is_none() || !b_idxs[..i].contains(b_idx),
+                "duplicate keys in get_disjoint_mut()"
+            );
+        }
+        let buckets = self.map.buckets.as_mut_ptr();
+        // unsafe is ok, the indices are within the buckets and distinct, so the references
+        // don't overlap
+        b_idxs.map(|b_idx| b_idx.map(|b_idx| unsafe { &mut (*buckets.add(b_idx)).value }))
+    }
+
+    /// Returns the key-value pair at the given index, see
+    /// [`iter_indexed()`](FcHashMap::iter_indexed) for the meaning of the index.
+    ///
+    /// Iterating by index instead of with an iterator keeps the map free for other calls in the
+    /// loop, e.g. lookups of related keys followed by a change of the current value with
+    /// [`get_index_mut()`](FcHashMap::get_index_mut).
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// // Node -> (parent, depth)
+    /// let mut nodes = FcHashMap::<u8, (u8, u8), 16>::new();
+    /// nodes.insert(0, (0, 0)).unwrap();
+    /// nodes.insert(1, (0, 0)).unwrap();
+    /// nodes.insert(2, (1, 0)).unwrap();
+    ///
+    /// for _ in 0..2 {
+    ///     for idx in 0..nodes.len() {
+    ///         let (&node, &(parent, _)) = nodes.get_index(idx).unwrap();
+    ///         if node != parent {
+    ///             let parent_depth = nodes.get(&parent).unwrap().1;
+    ///             let (_, value) = nodes.get_index_mut(idx).unwrap();
+    ///             value.1 = parent_depth + 1;
+    ///         }
+    ///     }
+    /// }
+    /// assert_eq!(nodes.get(&2), Some(&(1, 2)));
+    /// ```
+    pub fn get_index(&self, idx: usize) -> Option<(&K, &V)> {
+        self.map
+            .buckets
+            .get(idx)
+            .map(|bucket| (&bucket.key, &bucket.value))
+    }
+
+    /// Returns the key and a mutable reference to the value at the given index, see
+    /// [`get_index()`](FcHashMap::get_index).
+    pub fn get_index_mut(&mut self, idx: usize) -> Option<(&K, &mut V)> {
+        self.map
+            .buckets
+            .get_mut(idx)
+            .map(|bucket| (&bucket.key, &mut bucket.value))
+    }
+
+    /// Returns the key-value pair corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but `Hash` and `Eq` on the borrowed
+    /// form must match those for the key type. The returned key is the one stored in the map.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert(1, "a").unwrap();
+    /// assert_eq!(map.get_key_value(&1), Some((&1, &"a")));
+    /// assert_eq!(map.get_key_value(&2), None);
+    /// ```
+    pub fn get_key_value<Q>(&self, key: &Q) -> Option<(&K, &V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.find(key).map(|(_, b_idx)| {
+            let bucket = &self.map.buckets[b_idx];
+            (&bucket.key, &bucket.value)
+        })
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    ///
+    /// The key may be any borrowed form of the map's key type, but `Hash` and `Eq` on the borrowed
+    /// form *must* match those for the key type.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 8>::new();
+    /// map.insert(1, "a").unwrap();
+    /// if let Some(x) = map.get_mut(&1) {
+    ///     *x = "b";
+    /// }
+    /// assert_eq!(map.get(&1), Some(&"b"));
+    /// ```
+    pub fn get_mut<'v, Q>(&'v mut self, key: &Q) -> Option<&'v mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.get_mut(key)
+    }
+
+    /// Returns the index of the entry with the given key, see
+    /// [`iter_indexed()`](FcHashMap::iter_indexed) for the meaning of the index.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 1).unwrap();
+    /// map.insert("b", 2).unwrap();
+    ///
+    /// let idx = map.index_of(&"b").unwrap();
+    /// assert_eq!(map.get_index(idx), Some((&"b", &2)));
+    /// ```
+    pub fn index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.find(key).map(|(_, b_idx)| b_idx)
+    }
+
+    /// Counts the entries per group, where `f` projects every entry to its group.
+    ///
+    /// The result is a map from the groups to the number of their entries, which saturates at
+    /// `u16::MAX`. If there are more than `N` groups, the first group that doesn't fit is
+    /// returned as error.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut devices = FcHashMap::<u32, &str, 16>::new();
+    /// devices.insert(1, "sensor").unwrap();
+    /// devices.insert(2, "actor").unwrap();
+    /// devices.insert(3, "sensor").unwrap();
+    ///
+    /// let histogram = devices.group_count_by::<_, _, 4>(|_, kind| *kind).unwrap();
+    /// assert_eq!(histogram.get(&"sensor"), Some(&2));
+    /// assert_eq!(histogram.get(&"actor"), Some(&1));
+    ///
+    /// assert_eq!(devices.group_count_by::<_, _, 2>(|id, _| *id).err(), Some(3));
+    /// ```
+    pub fn group_count_by<G, F, const N: usize>(&self, mut f: F) -> Result<FcHashMap<G, u16, N>, G>
+    where
+        G: Hash + Eq,
+        F: FnMut(&K, &V) -> G,
+    {
+        let mut counts = FcHashMap::new();
+        for (key, value) in self.iter() {
+            let count = counts.entry(f(key, value))?.or_insert(0);
+            *count = count.saturating_add(1);
+        }
+        Ok(counts)
+    }
+
+    /// Returns a mutable reference to the value of the key, after inserting the result of
+    /// `default` if the key is not in the map.
+    ///
+    /// The map is searched only once. If the key is not in the map and the map is full, the key
+    /// is given back in a [`CapacityError`] and `default` is not called.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// // Connection -> packet counter
+    /// let mut connections = FcHashMap::<(u32, u16), u32, 2>::new();
+    /// for conn in [(1, 80), (2, 443), (1, 80)].iter() {
+    ///     *connections.get_or_insert_with(*conn, || 0).unwrap() += 1;
+    /// }
+    /// assert_eq!(connections.get(&(1, 80)), Some(&2));
+    ///
+    /// let err = connections.get_or_insert_with((3, 22), || 0).unwrap_err();
+    /// assert_eq!(err.key, (3, 22));
+    /// ```
+    pub fn get_or_insert_with<F>(
+        &mut self,
+        key: K,
+        default: F,
+    ) -> Result<&mut V, CapacityError<K>>
+    where
+        K: Hash + Eq,
+        F: FnOnce() -> V,
+    {
+        match self.entry(key) {
+            Ok(entry) => Ok(entry.or_insert_with(default)),
+            Err(key) => Err(CapacityError { key, value: () }),
+        }
+    }
+
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If an equivalent key already exists in the map: the key remains and retains in its place in
+    /// the order, its corresponding value is updated with `value` and the older value is returned
+    /// inside `Some(_)`.
+    ///
+    /// If no equivalent key existed in the map: the new key-value pair is inserted, and `None`
+    /// is returned.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 8>::new();
+    /// assert_eq!(map.insert(37, "a"), Ok(None));
+    ///
+    /// map.insert(37, "b");
+    /// assert_eq!(map.insert(37, "c"), Ok(Some("b")));
+    /// assert_eq!(map.get(&37), Some(&"c"));
+    /// ```
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)>
+    where
+        K: Hash + PartialEq,
+    {
+        self.map.insert(key, value)
+    }
+
+    /// Inserts a key-value pair into the map and returns a mutable reference to the value.
+    ///
+    /// If the key is already in the map, its value is replaced and the old value is dropped.
+    /// Unlike [`insert()`](FcHashMap::insert), this also works on a full map if the key is
+    /// already in it. If the key is not in the map and the map is full, the key-value pair is
+    /// given back.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// struct Channel {
+    ///     baud: u32,
+    ///     parity: bool,
+    /// }
+    ///
+    /// let mut channels = FcHashMap::<u8, Channel, 4>::new();
+    /// let channel = channels
+    ///     .insert_and_get(1, Channel { baud: 9600, parity: false })
+    ///     .ok()
+    ///     .unwrap();
+    /// channel.parity = true;
+    ///
+    /// assert!(channels.get(&1).unwrap().parity);
+    /// ```
+    pub fn insert_and_get(&mut self, key: K, value: V) -> Result<&mut V, (K, V)>
+    where
+        K: Hash + Eq,
+    {
+        match self.entry(key) {
+            Ok(Entry::Occupied(entry)) => {
+                let slot = entry.into_mut();
+                *slot = value;
+                Ok(slot)
+            }
+            Ok(Entry::Vacant(entry)) => Ok(entry.insert(value)),
+            Err(key) => Err((key, value)),
+        }
+    }
+
+    /// Inserts a key-value pair into the map, or merges the value into the stored one if the key
+    /// is already in the map. Returns a mutable reference to the stored value.
+    ///
+    /// The map is searched only once. `merge` is called with the stored value and the given
+    /// one. If the key is not in the map and the map is full, the key-value pair is given back.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// // Port -> (packets, bytes)
+    /// let mut traffic = FcHashMap::<u16, (u32, u32), 16>::new();
+    /// for (port, len) in [(80, 60), (443, 1500), (80, 40)].iter() {
+    ///     traffic
+    ///         .insert_or_merge(*port, (1, *len), |old, new| {
+    ///             old.0 += new.0;
+    ///             old.1 += new.1;
+    ///         })
+    ///         .unwrap();
+    /// }
+    /// assert_eq!(traffic.get(&80), Some(&(2, 100)));
+    /// ```
+    pub fn insert_or_merge<F>(&mut self, key: K, value: V, merge: F) -> Result<&mut V, (K, V)>
+    where
+        K: Hash + Eq,
+        F: FnOnce(&mut V, V),
+    {
+        match self.entry(key) {
+            Ok(Entry::Occupied(entry)) => {
+                let stored = entry.into_mut();
+                merge(stored, value);
+                Ok(stored)
+            }
+            Ok(Entry::Vacant(entry)) => Ok(entry.insert(value)),
+            Err(key) => Err((key, value)),
+        }
+    }
+
+    /// Inserts a key-value pair into the map, or suggests an entry to evict if the map is full.
+    ///
+    /// This works like [`insert()`](FcHashMap::insert), but a full map does not just give back the
+    /// key-value pair. The eviction policy `rank` is called for every entry and the entry with the
+    /// lowest rank is returned as [`Suggestion`], together with the pair. The map is not changed,
+    /// so the caller decides whether to evict. If the key is already in the map, its value is
+    /// replaced even if the map is full.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// // Sensor id -> (reading, timestamp of last update)
+    /// let mut map = FcHashMap::<u8, (i16, u32), 2>::new();
+    /// map.insert(1, (20, 100)).unwrap();
+    /// map.insert(2, (21, 50)).unwrap();
+    ///
+    /// // Suggest the least recently updated entry
+    /// let suggestion = map.insert_or_suggest(3, (22, 150), |_, v| v.1).unwrap_err();
+    /// assert_eq!(suggestion.victim, 2);
+    ///
+    /// map.remove(&suggestion.victim);
+    /// map.insert(suggestion.key, suggestion.value).unwrap();
+    /// assert!(map.contains_key(&3));
+    /// ```
+    pub fn insert_or_suggest<B, F>(
+        &mut self,
+        key: K,
+        value: V,
+        rank: F,
+    ) -> Result<Option<V>, Suggestion<K, V>>
+    where
+        K: Hash + Eq + Clone,
+        F: FnMut(&K, &V) -> B,
+        B: Ord,
+    {
+        match self.map.find_or_hash(&key) {
+            Ok((_, b_idx)) => {
+                Ok(Some(core::mem::replace(&mut self.map.buckets[b_idx].value, value)))
+            }
+            Err(_) if self.map.buckets.is_full() => {
+                // A capacity of 0 is rejected at compile time, so a full map has a victim
+                let (victim, _) = self.min_by_key(rank).expect("a full map has entries");
+                let victim = victim.clone();
+                Err(Suggestion { key, value, victim })
+            }
+            Err(hash) => {
+                let _ = self.map.insert_hashed(key, value, hash);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Inserts a key-value pair into the map, if the key passes its validation.
+    ///
+    /// This works like [`insert()`](FcHashMap::insert), but calls
+    /// [`ValidateKey::validate()`](key::ValidateKey::validate) first in debug builds. If the key
+    /// is not valid, the key-value pair is given back as error without touching the map. With the
+    /// `hardening` feature, keys are validated in release builds as well. Otherwise a release
+    /// build inserts like `insert()`, without the cost of the validation.
+    ///
+    /// ## Example
+    ///
+    /// See [`ValidateKey`](key::ValidateKey).
+    pub fn insert_validated(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)>
+    where
+        K: Hash + PartialEq + key::ValidateKey,
+    {
+        #[cfg(any(debug_assertions, feature = "hardening"))]
+        if !key.validate() {
+            return Err((key, value));
+        }
+        self.map.insert(key, value)
+    }
+
+    /// Consumes the map and returns an iterator over its keys, in the order of the map.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 1).unwrap();
+    /// map.insert("b", 2).unwrap();
+    ///
+    /// let keys: Vec<&str> = map.into_keys().collect();
+    /// assert_eq!(keys, vec!["a", "b"]);
+    /// ```
+    pub fn into_keys(self) -> impl ExactSizeIterator<Item = K> + FusedIterator {
+        self.into_iter().map(|(key, _)| key)
+    }
+
+    /// Consumes the map and returns an iterator over its values, in the order of the map.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert(1, vec![0xaa]).unwrap();
+    /// map.insert(2, vec![0xbb, 0xcc]).unwrap();
+    ///
+    /// let payloads: Vec<Vec<u8>> = map.into_values().collect();
+    /// assert_eq!(payloads, vec![vec![0xaa], vec![0xbb, 0xcc]]);
+    /// ```
+    pub fn into_values(self) -> impl ExactSizeIterator<Item = V> + FusedIterator {
+        self.into_iter().map(|(_, value)| value)
+    }
+
+    /// Returns a map from the values to the keys of this map.
+    ///
+    /// If several keys have the same value, `duplicates` decides which of them is kept, or if
+    /// a [`CollisionError`] is returned. The inverted map never has more entries than this map,
+    /// so it always fits.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::{Duplicates, FcHashMap};
+    ///
+    /// // Symbol -> code
+    /// let encode: FcHashMap<char, u8, 8> = "abc".chars().zip(0..).collect();
+    /// let decode = encode.invert(Duplicates::Reject).unwrap();
+    /// assert_eq!(decode.get(&1), Some(&'b'));
+    ///
+    /// let lossy: FcHashMap<char, u8, 8> = [('a', 0), ('A', 0)].iter().copied().collect();
+    /// assert!(lossy.invert(Duplicates::Reject).is_err());
+    /// assert_eq!(lossy.invert(Duplicates::KeepFirst).unwrap().get(&0), Some(&'a'));
+    /// assert_eq!(lossy.invert(Duplicates::KeepLast).unwrap().get(&0), Some(&'A'));
+    /// ```
+    pub fn invert(&self, duplicates: Duplicates) -> Result<FcHashMap<V, K, CAP>, CollisionError>
+    where
+        K: Clone,
+        V: Clone + Hash + Eq,
+    {
+        let mut inverted = FcHashMap::new();
+        for (key, value) in self.iter() {
+            if duplicates == Duplicates::KeepFirst && inverted.contains_key(value) {
+                continue;
+            }
+            // The inverted map has at most as many entries as this map, so the insert can't fail
+            let replaced = inverted.insert(value.clone(), key.clone());
+            debug_assert!(replaced.is_ok());
+            if duplicates == Duplicates::Reject && matches!(replaced, Ok(Some(_))) {
+                return Err(CollisionError);
+            }
+        }
+        Ok(inverted)
+    }
+
+    /// Returns true if the map contains no elements.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// assert_eq!(map.is_empty(), true);
+    ///
+    /// map.insert(1, "a");
+    /// assert_eq!(map.is_empty(), false);
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.buckets.is_empty()
+    }
+
+    /// Return an iterator over the key-value pairs of the map, in their order.
+    ///
+    /// The iterator can be reversed. As long as no entry has been removed, this yields the most
+    /// recently inserted entries first.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 1).unwrap();
+    /// map.insert("b", 2).unwrap();
+    /// map.insert("c", 3).unwrap();
+    ///
+    /// let v: Vec<_> = map.iter().collect();
+    /// assert_eq!(v, vec![(&"a", &1), (&"b", &2), (&"c", &3)]);
+    ///
+    /// let newest: Vec<_> = map.iter().rev().take(2).collect();
+    /// assert_eq!(newest, vec![(&"c", &3), (&"b", &2)]);
+    /// ```
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            iter: self.map.buckets.iter(),
+        }
+    }
+
+    /// Return an iterator over the key-value pairs of the map in chunks of `N` pairs, in the
+    /// order of the map.
+    ///
+    /// Every chunk is an [`Iter`] over `N` consecutive entries of the storage of the map, only the
+    /// last one may hold fewer. The entries are not copied. Keys and values are stored side by
+    /// side, so a chunk is not a slice of values that could be handed to a routine expecting one.
+    /// A chunk size of 0 fails to compile.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let map: FcHashMap<u32, i32, 16> = (0..10).map(|i| (i, i as i32)).collect();
+    ///
+    /// let mut sums = Vec::new();
+    /// for chunk in map.iter_chunks::<4>() {
+    ///     sums.push(chunk.map(|(_, v)| *v).sum::<i32>());
+    /// }
+    /// assert_eq!(sums.len(), 3);
+    /// assert_eq!(sums.iter().sum::<i32>(), 45);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let map = FcHashMap::<u32, i32, 16>::new();
+    /// map.iter_chunks::<0>().count();
+    /// ```
+    pub fn iter_chunks<const N: usize>(&self) -> Chunks<'_, K, V, N> {
+        Chunks::new(&self.map.buckets)
+    }
+
+    /// Return an iterator over the index and the key-value pairs of the map, in their order.
+    ///
+    /// The entries of the map are stored densely, and the index is the position of an entry in
+    /// this storage. It is guaranteed to be the same as the position of the entry in `iter()`,
+    /// `keys()` and `values()`, so side tables indexed in parallel to the map stay in step with it.
+    /// Inserting new keys appends them at the end. Removing an entry moves the last entry into its
+    /// place, so side tables must do the same.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 1).unwrap();
+    /// map.insert("b", 2).unwrap();
+    ///
+    /// let v: Vec<_> = map.iter_indexed().collect();
+    /// assert_eq!(v, vec![(0, &"a", &1), (1, &"b", &2)]);
+    /// ```
+    pub fn iter_indexed(&self) -> impl ExactSizeIterator<Item = (usize, &K, &V)> + FusedIterator {
+        self.map
+            .buckets
+            .iter()
+            .enumerate()
+            .map(|(idx, bucket)| (idx, &bucket.key, &bucket.value))
+    }
+
+    /// Return an iterator over the key-value pairs of the map, in their order.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 1).unwrap();
+    /// map.insert("b", 2).unwrap();
+    /// map.insert("c", 3).unwrap();
+    ///
+    /// for (_, val) in map.iter_mut() {
+    ///     *val = 23;
+    /// }
+    ///
+    /// let v: Vec<_> = map.iter().collect();
+    /// assert_eq!(v, vec![(&"a", &23), (&"b", &23), (&"c", &23)]);
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            iter: self.map.buckets.iter_mut(),
+        }
+    }
+
+    /// Returns the number of full key comparisons since the creation of the map or the last call
+    /// of [`reset_key_comparisons()`](FcHashMap::reset_key_comparisons).
+    ///
+    /// Keys are only compared if their hash values match. For large keys, these comparisons
+    /// rather than the probes in the hash table dominate the cost of a lookup. The counter wraps
+    /// around on overflow.
+    ///
+    /// Only available with the `stats` feature. The counter is kept in a `Cell`, so the map is no
+    /// longer `Sync` with this feature.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert(1, "a").unwrap();
+    /// map.reset_key_comparisons();
+    ///
+    /// map.get(&1);
+    /// assert_eq!(map.key_comparisons(), 1);
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn key_comparisons(&self) -> u32 {
+        self.map.comparisons()
+    }
+
+    /// Return an iterator over the keys of the map, in their order.
+    ///
+    /// The order matches the order of [`entries()`](FcHashMap::entries) and `values()`.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 1).unwrap();
+    /// map.insert("b", 2).unwrap();
+    /// map.insert("c", 3).unwrap();
+    ///
+    /// let v: Vec<_> = map.keys().collect();
+    /// assert_eq!(v, vec![&"a", &"b", &"c"]);
+    /// ```
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        Keys {
+            iter: self.map.buckets.iter(),
+        }
+    }
+
+    /// Return an iterator over the keys of all entries with the value, in the order of the map.
+    ///
+    /// The map is indexed by its keys, so this is a linear scan over all entries.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// // Symbol -> address
+    /// let mut symbols = FcHashMap::<_, _, 16>::new();
+    /// symbols.insert("main", 0x0800_0100).unwrap();
+    /// symbols.insert("reset_handler", 0x0800_0004).unwrap();
+    /// symbols.insert("_start", 0x0800_0004).unwrap();
+    ///
+    /// let v: Vec<_> = symbols.keys_for_value(&0x0800_0004).collect();
+    /// assert_eq!(v, vec![&"reset_handler", &"_start"]);
+    /// ```
+    pub fn keys_for_value<'a>(&'a self, value: &'a V) -> impl Iterator<Item = &'a K> + 'a
+    where
+        V: PartialEq,
+    {
+        self.iter()
+            .filter(move |(_, v)| *v == value)
+            .map(|(k, _)| k)
+    }
+
+    /// Return the number of key-value pairs in the map.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// assert_eq!(map.len(), 0);
+    ///
+    /// map.insert(1, "a").unwrap();
+    /// assert_eq!(map.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.buckets.len()
+    }
+
+    /// Returns the load of the map, the number of entries divided by the capacity.
+    ///
+    /// Above a load of about 0.8, the map gets slow, see [Performance](FcHashMap#performance).
+    /// On targets without an FPU, [`load_percent()`](FcHashMap::load_percent) avoids floating
+    /// point arithmetic.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<u32, u32, 16>::new();
+    /// for i in 0..12 {
+    ///     map.insert(i, i).unwrap();
+    /// }
+    /// assert_eq!(map.load_factor(), 0.75);
+    /// ```
+    pub fn load_factor(&self) -> f32 {
+        self.len() as f32 / CAP as f32
+    }
+
+    /// Returns the load of the map in percent of its capacity, rounded down.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<u32, u32, 16>::new();
+    /// for i in 0..13 {
+    ///     map.insert(i, i).unwrap();
+    /// }
+    /// if map.load_percent() >= 80 {
+    ///     map.retain(|&k, _| k % 2 == 0);
+    /// }
+    /// assert_eq!(map.load_percent(), 43);
+    /// ```
+    pub fn load_percent(&self) -> usize {
+        self.len() * 100 / CAP
+    }
+
+    /// Consumes the map and returns a map with the keys transformed by `f` and the same values.
+    ///
+    /// All entries are inserted into a new hash table under their new keys. If `f` maps two keys
+    /// to the same new key, a [`CollisionError`] is returned and the entries are dropped.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::{CollisionError, FcHashMap};
+    ///
+    /// // Wire id -> name
+    /// let mut wire = FcHashMap::<u32, &str, 16>::new();
+    /// wire.insert(0x1000_0001, "pump").unwrap();
+    /// wire.insert(0x1000_0002, "valve").unwrap();
+    ///
+    /// let internal = wire.clone().map_keys(|id| (id & 0xff) as u8).unwrap();
+    /// assert_eq!(internal.get(&2), Some(&"valve"));
+    ///
+    /// assert_eq!(wire.map_keys(|id| (id >> 16) as u16).err(), Some(CollisionError));
+    /// ```
+    pub fn map_keys<K2, F>(self, mut f: F) -> Result<FcHashMap<K2, V, CAP>, CollisionError>
+    where
+        K2: Hash + Eq,
+        F: FnMut(K) -> K2,
+    {
+        let mut map = FcHashMap::new();
+        for bucket in self.map.buckets {
+            match map.insert(f(bucket.key), bucket.value) {
+                Ok(None) => {}
+                _ => return Err(CollisionError),
+            }
+        }
+        Ok(map)
+    }
+
+    /// Consumes the map and returns a map with the same keys and the values transformed by `f`.
+    ///
+    /// The keys keep their hash values and places, so no key is hashed again and no probing is
+    /// done. `f` is called once per entry, in the order of the map.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut raw = FcHashMap::<u8, u16, 16>::new();
+    /// raw.insert(1, 2048).unwrap();
+    /// raw.insert(2, 1024).unwrap();
+    ///
+    /// let volts = raw.map_values(|_, adc| f32::from(adc) * 3.3 / 4096.0);
+    /// assert_eq!(volts.get(&1), Some(&1.65));
+    /// assert_eq!(volts.get(&2), Some(&0.825));
+    /// ```
+    pub fn map_values<U, F>(self, f: F) -> FcHashMap<K, U, CAP>
+    where
+        F: FnMut(&K, V) -> U,
+    {
+        FcHashMap {
+            map: self.map.map_values(f),
+        }
+    }
+
+    /// Returns a map with the same keys and the values transformed by `f`, and keeps this map.
+    ///
+    /// Like [`map_values()`](FcHashMap::map_values), the keys keep their hash values and places,
+    /// but they are cloned.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut raw = FcHashMap::<u8, u16, 16>::new();
+    /// raw.insert(1, 2048).unwrap();
+    ///
+    /// let volts = raw.map_values_ref(|_, adc| f32::from(*adc) * 3.3 / 4096.0);
+    /// assert_eq!(volts.get(&1), Some(&1.65));
+    /// assert_eq!(raw.get(&1), Some(&2048));
+    /// ```
+    pub fn map_values_ref<U, F>(&self, f: F) -> FcHashMap<K, U, CAP>
+    where
+        K: Clone,
+        F: FnMut(&K, &V) -> U,
+    {
+        FcHashMap {
+            map: self.map.map_values_ref(f),
+        }
+    }
+
+    /// Returns the entry with the maximum metric calculated by `f`.
+    ///
+    /// If several entries share the maximum metric, the entry that comes first in the order of the
+    /// map is returned. In contrast to `Iterator::max_by_key()`, this tie-breaking is guaranteed,
+    /// so the result is deterministic for the same sequence of operations on the map.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 1).unwrap();
+    /// map.insert("b", 3).unwrap();
+    /// map.insert("c", 3).unwrap();
+    ///
+    /// assert_eq!(map.max_by_key(|_, v| *v), Some((&"b", &3)));
+    /// ```
+    pub fn max_by_key<B, F>(&self, mut f: F) -> Option<(&K, &V)>
+    where
+        F: FnMut(&K, &V) -> B,
+        B: Ord,
+    {
+        let mut best: Option<(B, &map::Bucket<K, V>)> = None;
+        for bucket in self.map.buckets.iter() {
+            let metric = f(&bucket.key, &bucket.value);
+            match best {
+                Some((ref best_metric, _)) if metric <= *best_metric => {}
+                _ => best = Some((metric, bucket)),
+            }
+        }
+        best.map(|(_, bucket)| (&bucket.key, &bucket.value))
+    }
+
+    /// Moves all entries of the map `other`, which may have a different capacity, into this map.
+    /// Returns the number of merged entries.
+    ///
+    /// If a key is in both maps, `resolve` is called with the value of this map and the value of
+    /// `other` and combines them. If this map gets full, the entries with new keys that don't
+    /// fit remain in `other`, as its only entries, and a [`MoveError`] counts them.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// // Packets per port, counted on two cores
+    /// let mut total: FcHashMap<u16, u32, 8> = [(80, 10), (443, 20)].iter().copied().collect();
+    /// let mut core1: FcHashMap<u16, u32, 8> = [(443, 5), (22, 1)].iter().copied().collect();
+    ///
+    /// assert_eq!(total.merge(&mut core1, |sum, count| *sum += count), Ok(2));
+    /// assert!(core1.is_empty());
+    /// assert_eq!(total.get(&443), Some(&25));
+    /// assert_eq!(total.get(&22), Some(&1));
+    ///
+    /// let mut core2: FcHashMap<u16, u32, 8> = (1000..1007).map(|port| (port, 1)).collect();
+    /// let err = total.merge(&mut core2, |sum, count| *sum += count).unwrap_err();
+    /// assert_eq!((err.moved, err.not_moved, core2.len()), (5, 2, 2));
+    /// ```
+    pub fn merge<F, const CAP2: usize>(
+        &mut self,
+        other: &mut FcHashMap<K, V, CAP2>,
+        mut resolve: F,
+    ) -> Result<usize, MoveError>
+    where
+        K: Hash + Eq,
+        F: FnMut(&mut V, V),
+    {
+        let mut moved = 0;
+        let mut not_moved = 0;
+        other.map.visit_rev(|other, b_idx| {
+            // The bucket of other already holds the hash value of the key, so the key is hashed
+            // and searched in this map only once
+            let hash = other.hash_of(b_idx);
+            let found = self.map.find_hashed(&other.buckets[b_idx].key, hash);
+            if found.is_none() && self.len() == CAP {
+                not_moved += 1;
+                return;
+            }
+            let h_idx = other.h_idx_of(b_idx);
+            let (key, value) = other.remove_found(h_idx, b_idx);
+            match found {
+                Some((_, found_b_idx)) => resolve(&mut self.map.buckets[found_b_idx].value, value),
+                None => {
+                    let inserted = self.map.insert_hashed(key, value, hash).is_ok();
+                    debug_assert!(inserted);
+                }
+            }
+            moved += 1;
+        });
+        if not_moved == 0 {
+            Ok(moved)
+        } else {
+            Err(MoveError { moved, not_moved })
+        }
+    }
+
+    /// Returns the entry with the minimum metric calculated by `f`.
+    ///
+    /// If several entries share the minimum metric, the entry that comes first in the order of the
+    /// map is returned. This tie-breaking is guaranteed, so the result is deterministic for the
+    /// same sequence of operations on the map.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 3).unwrap();
+    /// map.insert("b", 1).unwrap();
+    /// map.insert("c", 1).unwrap();
+    ///
+    /// assert_eq!(map.min_by_key(|_, v| *v), Some((&"b", &1)));
+    /// ```
+    pub fn min_by_key<B, F>(&self, mut f: F) -> Option<(&K, &V)>
+    where
+        F: FnMut(&K, &V) -> B,
+        B: Ord,
+    {
+        let mut best: Option<(B, &map::Bucket<K, V>)> = None;
+        for bucket in self.map.buckets.iter() {
+            let metric = f(&bucket.key, &bucket.value);
+            match best {
+                Some((ref best_metric, _)) if metric >= *best_metric => {}
+                _ => best = Some((metric, bucket)),
+            }
+        }
+        best.map(|(_, bucket)| (&bucket.key, &bucket.value))
+    }
+
+    /// Calls `f` for every key and a mutable reference to its value, in the order of the map.
+    ///
+    /// This does the same as a `for` loop over `iter_mut()`, but is written as a plain indexed
+    /// loop. In builds optimized for size, this avoids iterator chains that are not always
+    /// optimized away on small targets.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert(1, 10).unwrap();
+    /// map.insert(2, 20).unwrap();
+    ///
+    /// map.modify_all(|k, v| *v *= k);
+    /// assert_eq!(map.get(&1), Some(&10));
+    /// assert_eq!(map.get(&2), Some(&40));
+    /// ```
+    pub fn modify_all<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V),
+    {
+        let buckets = self.map.buckets.as_mut_slice();
+        let mut idx = 0;
+        while idx < buckets.len() {
+            let bucket = &mut buckets[idx];
+            f(&bucket.key, &mut bucket.value);
+            idx += 1;
+        }
+    }
+
+    /// Removes the most recently inserted entry and returns it, or `None` if the map is empty.
+    ///
+    /// The entries are stored in the order of their insertion, as long as no entry is removed.
+    /// Removing an entry moves the last entry into its place. `pop()` removes the last entry of
+    /// [`iter()`](FcHashMap::iter), which needs no other entry to move.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 1).unwrap();
+    /// map.insert("b", 2).unwrap();
+    ///
+    /// assert_eq!(map.pop(), Some(("b", 2)));
+    /// assert_eq!(map.pop(), Some(("a", 1)));
+    /// assert_eq!(map.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        let b_idx = self.len().checked_sub(1)?;
+        let h_idx = self.map.h_idx_of(b_idx);
+        Some(self.map.remove_found(h_idx, b_idx))
+    }
+
+    /// Reads every place of the hash table and every entry, so that they are in the cache
+    /// afterwards.
+    ///
+    /// On systems with a data cache (e.g. Cortex-M7 or Cortex-A), the first accesses to a map
+    /// after boot suffer from cache misses. Calling this method during startup moves these misses
+    /// out of the first time critical access. The reads are volatile and can't be optimized away.
+    /// Of every entry only one word is read, so entries that are larger than a cache line are
+    /// only partially loaded.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<u32, u32, 64>::new();
+    /// map.insert(1, 10).unwrap();
+    ///
+    /// map.prewarm();
+    /// assert_eq!(map.get(&1), Some(&10));
+    /// ```
+    pub fn prewarm(&self) {
+        self.map.prewarm();
+    }
+
+    /// Measures how many places of the hash table lookups read with the current content of the
+    /// map, see [`ProbeStats`].
+    ///
+    /// This takes a pass over the hash table. The numbers depend on the load of the map and on
+    /// how well the hash values of the keys spread. The tests of this crate check them for
+    /// random keys, see [Performance](FcHashMap#performance).
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<u32, u32, 1024>::new();
+    /// for i in 0..768u32 {
+    ///     map.insert(i.wrapping_mul(2_654_435_761), i).unwrap();
+    /// }
+    ///
+    /// let stats = map.probe_stats();
+    /// assert_eq!(stats.len, 768);
+    /// assert!(stats.mean_hit_probes() < 3.0);
+    /// ```
+    pub fn probe_stats(&self) -> ProbeStats {
+        self.map.probe_stats()
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously
+    /// in the map.
+    ///
+    /// The key may be any borrowed form of the map’s key type, but Hash and Eq on the borrowed
+    /// form must match those for the key type.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove(&1), Some("a"));
+    /// assert_eq!(map.remove(&1), None);
+    /// ```
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.remove(key)
+    }
+
+    /// Removes a key from the map, returning the stored key and its value if the key was
+    /// previously in the map.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert(1, "a");
+    /// assert_eq!(map.remove_entry(&1), Some((1, "a")));
+    /// assert_eq!(map.remove_entry(&1), None);
+    /// ```
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.remove_entry(key)
+    }
+
+    /// Removes a key from the map if `f` returns true for its value, returning the value.
+    ///
+    /// The key is searched only once. If the key is not in the map or `f` returns false, the
+    /// map remains unchanged and `None` is returned.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// // Session id -> reference count
+    /// let mut sessions = FcHashMap::<u16, u8, 16>::new();
+    /// sessions.insert(7, 2).unwrap();
+    ///
+    /// assert_eq!(sessions.remove_if(&7, |refs| *refs == 0), None);
+    /// *sessions.get_mut(&7).unwrap() = 0;
+    /// assert_eq!(sessions.remove_if(&7, |refs| *refs == 0), Some(0));
+    /// assert!(sessions.is_empty());
+    /// ```
+    pub fn remove_if<Q, F>(&mut self, key: &Q, f: F) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+        F: FnOnce(&V) -> bool,
+    {
+        let (h_idx, b_idx) = self.map.find(key)?;
+        if f(&self.map.buckets[b_idx].value) {
+            Some(self.map.remove_found(h_idx, b_idx).1)
+        } else {
+            None
+        }
+    }
+
+    /// Resets the number of full key comparisons to zero.
+    ///
+    /// Only available with the `stats` feature.
+    #[cfg(feature = "stats")]
+    pub fn reset_key_comparisons(&self) {
+        self.map.reset_comparisons();
+    }
+
+    /// Retains only the entries for which `f` returns true and removes all others.
+    ///
+    /// This is done in a single pass over the map, without a buffer for the keys to be removed.
+    /// `f` is called exactly once for every entry and may change its value.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map: FcHashMap<u32, u32, 16> = (0..8).map(|i| (i, i * 10)).collect();
+    /// map.retain(|&k, v| {
+    ///     *v += 1;
+    ///     k % 2 == 0
+    /// });
+    ///
+    /// assert_eq!(map.len(), 4);
+    /// assert_eq!(map.get(&4), Some(&41));
+    /// assert_eq!(map.get(&5), None);
+    /// ```
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.map.retain(f)
+    }
+
+    /// Returns the sum of all values of the map.
+    ///
+    /// The addition saturates at the numeric bounds of `V`, so a map of counters can be summed up
+    /// without the risk of an overflow panic.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 200_u8).unwrap();
+    /// map.insert("b", 20_u8).unwrap();
+    /// assert_eq!(map.sum_values(), 220);
+    ///
+    /// map.insert("c", 100_u8).unwrap();
+    /// assert_eq!(map.sum_values(), u8::MAX);
+    /// ```
+    pub fn sum_values(&self) -> V
+    where
+        V: SaturatingAdd,
+    {
+        self.fold_values(V::ZERO, |acc, v| acc.saturating_add(*v))
+    }
+
+    /// Exchanges the values of two keys. Returns false and leaves the map unchanged if one of
+    /// the keys is not in the map.
+    ///
+    /// Only the values move, the keys keep their places in the map. For mutable references to
+    /// the values of several keys, see [`get_disjoint_mut()`](FcHashMap::get_disjoint_mut).
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// // Buffer -> DMA channel
+    /// let mut channels = FcHashMap::<&str, u8, 8>::new();
+    /// channels.insert("rx", 1).unwrap();
+    /// channels.insert("tx", 2).unwrap();
+    ///
+    /// assert!(channels.swap_values(&"rx", &"tx"));
+    /// assert_eq!(channels.get(&"rx"), Some(&2));
+    /// assert_eq!(channels.get(&"tx"), Some(&1));
+    /// assert!(!channels.swap_values(&"rx", &"spi"));
+    /// ```
+    pub fn swap_values<Q>(&mut self, k1: &Q, k2: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (b_idx1, b_idx2) = match (self.map.find(k1), self.map.find(k2)) {
+            (Some((_, b_idx1)), Some((_, b_idx2))) => (b_idx1, b_idx2),
+            _ => return false,
+        };
+        if b_idx1 != b_idx2 {
+            let (low, high) = (b_idx1.min(b_idx2), b_idx1.max(b_idx2));
+            let (front, back) = self.map.buckets.split_at_mut(high);
+            mem::swap(&mut front[low].value, &mut back[0].value);
+        }
+        true
+    }
+
+    /// Returns the `N` entries with the largest metric, ordered from largest to smallest.
+    ///
+    /// The metric is calculated by `f` for every value. Only `N` entries are kept during the
+    /// single pass over the map, so the map is never sorted as a whole. Entries with equal
+    /// metrics are returned in their order in the map.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use arrayvec::ArrayVec;
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 10).unwrap();
+    /// map.insert("b", 40).unwrap();
+    /// map.insert("c", 30).unwrap();
+    /// map.insert("d", 20).unwrap();
+    ///
+    /// let top: ArrayVec<_, 2> = map.top_n_by(|v| *v);
+    /// assert_eq!(top.as_slice(), &[(&"b", &40), (&"c", &30)]);
+    /// ```
+    pub fn top_n_by<M, F, const N: usize>(&self, f: F) -> ArrayVec<(&K, &V), N>
+    where
+        F: Fn(&V) -> M,
+        M: Ord,
+    {
+        let mut top = ArrayVec::<(&K, &V), N>::new();
+        let mut metrics = ArrayVec::<M, N>::new();
+        for bucket in self.map.buckets.iter() {
+            let metric = f(&bucket.value);
+            // Behind all entries with a greater or equal metric, this keeps the order stable
+            let pos = metrics.iter().position(|m| *m < metric).unwrap_or(metrics.len());
+            if pos == N {
+                continue;
+            }
+            if metrics.is_full() {
+                metrics.pop();
+                top.pop();
+            }
+            metrics.insert(pos, metric);
+            top.insert(pos, (&bucket.key, &bucket.value));
+        }
+        top
+    }
+
+    /// Removes entries until only `len` entries remain. Nothing happens if the map holds `len`
+    /// entries or less.
+    ///
+    /// The most recently inserted entries are removed first, in the same order as by
+    /// [`pop()`](FcHashMap::pop). This sheds load quickly if the map gets so full that it becomes
+    /// slow.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map: FcHashMap<u32, u32, 16> = (0..14).map(|i| (i, i)).collect();
+    /// map.truncate(12);
+    ///
+    /// assert_eq!(map.len(), 12);
+    /// assert_eq!(map.get(&11), Some(&11));
+    /// assert_eq!(map.get(&12), None);
+    /// ```
+    pub fn truncate(&mut self, len: usize) {
+        while self.len() > len {
+            self.pop();
+        }
+    }
+
+    /// Inserts the key-value pairs of an iterator until the map is full. Returns the number of
+    /// inserted pairs, including the pairs that replaced the value of a key already in the map.
+    ///
+    /// In contrast to `extend()`, which panics on a full map, the first pair with a new key that
+    /// doesn't fit is given back in an [`ExtendError`]. A pair whose key is already in the map
+    /// replaces its value, even if the map is full. The iterator is not advanced behind the
+    /// rejected pair, so passing it with `by_ref()` keeps the remaining pairs for later.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<u32, u32, 4>::new();
+    /// assert_eq!(map.try_extend([(1, 10), (2, 20), (1, 11)].iter().copied()), Ok(3));
+    ///
+    /// let mut pairs = (3..10).map(|k| (k, k * 10));
+    /// let err = map.try_extend(pairs.by_ref()).unwrap_err();
+    /// assert_eq!((err.inserted, err.key, err.value), (2, 5, 50));
+    /// assert_eq!(map.len(), 4);
+    /// assert_eq!(pairs.next(), Some((6, 60)));
+    ///
+    /// assert_eq!(map.try_extend([(1, 12)].iter().copied()), Ok(1));
+    /// assert_eq!(map.get(&1), Some(&12));
+    /// ```
+    pub fn try_extend<I>(&mut self, iterable: I) -> Result<usize, ExtendError<K, V>>
+    where
+        K: Hash + Eq,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut inserted = 0;
+        for (key, value) in iterable {
+            if let Err((key, value)) = self.insert(key, value) {
+                // A full map rejects every key, but the value of a known key can still be replaced
+                match self.get_mut(&key) {
+                    Some(existing) => *existing = value,
+                    None => {
+                        return Err(ExtendError {
+                            inserted,
+                            key,
+                            value,
+                        })
+                    }
+                }
+            }
+            inserted += 1;
+        }
+        Ok(inserted)
+    }
+
+    /// Creates a map from the key-value pairs of an iterator, or fails if they don't fit.
+    ///
+    /// In contrast to `collect()`, which panics on a full map, the first pair that doesn't fit
+    /// is given back in an [`ExtendError`] together with its position. Later pairs with the same
+    /// key replace the value of an earlier one, even if the map is full.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// // Parameter id -> value, read from flash
+    /// let blob = [(1u8, 100u16), (2, 200), (3, 300)];
+    ///
+    /// let params = FcHashMap::<u8, u16, 4>::try_from_iter(blob.iter().copied()).unwrap();
+    /// assert_eq!(params.get(&2), Some(&200));
+    ///
+    /// let err = FcHashMap::<u8, u16, 2>::try_from_iter(blob.iter().copied()).unwrap_err();
+    /// assert_eq!((err.inserted, err.key, err.value), (2, 3, 300));
+    /// ```
+    pub fn try_from_iter<I>(iterable: I) -> Result<Self, ExtendError<K, V>>
+    where
+        K: Hash + Eq,
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let mut map = FcHashMap::new();
+        map.try_extend(iterable)?;
+        Ok(map)
+    }
+
+    /// Builds a map from a slice of key-value pairs, rejecting duplicate keys.
+    ///
+    /// This works like `FcHashMap::try_from(pairs)`, but a key that occurs twice is an error
+    /// instead of replacing the earlier value. Neither panics if the pairs don't fit into the
+    /// map.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::{FcHashMap, FromSliceError};
+    /// use std::convert::TryFrom;
+    ///
+    /// let config: &[(u8, u16)] = &[(1, 100), (2, 200), (1, 300)];
+    ///
+    /// let map = FcHashMap::<u8, u16, 4>::try_from(config).unwrap();
+    /// assert_eq!(map.get(&1), Some(&300));
+    ///
+    /// let strict = FcHashMap::<u8, u16, 4>::try_from_slice_strict(config);
+    /// assert_eq!(strict.err(), Some(FromSliceError::Duplicate { index: 2 }));
+    ///
+    /// let small = FcHashMap::<u8, u16, 1>::try_from(config);
+    /// assert_eq!(small.err(), Some(FromSliceError::Full { index: 1 }));
+    /// ```
+    pub fn try_from_slice_strict(pairs: &[(K, V)]) -> Result<Self, FromSliceError>
+    where
+        K: Hash + Eq + Clone,
+        V: Clone,
+    {
+        let mut map = FcHashMap::new();
+        for (index, (key, value)) in pairs.iter().enumerate() {
+            match map.try_insert(key.clone(), value.clone()) {
+                Ok(_) => {}
+                Err(TryInsertError::Occupied { .. }) => {
+                    return Err(FromSliceError::Duplicate { index })
+                }
+                Err(TryInsertError::Full { .. }) => return Err(FromSliceError::Full { index }),
+            }
+        }
+        Ok(map)
+    }
+
+    /// Inserts a key-value pair into the map, if the key is not yet in the map.
+    ///
+    /// Unlike [`insert()`](FcHashMap::insert), an existing value is never overwritten. If the key
+    /// is already in the map or the map is full, the key-value pair is given back in a
+    /// [`TryInsertError`], together with a reference to the existing value. On success, a
+    /// mutable reference to the inserted value is returned.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::{FcHashMap, TryInsertError};
+    ///
+    /// let mut drivers = FcHashMap::<u8, &str, 2>::new();
+    /// assert_eq!(drivers.try_insert(1, "uart"), Ok(&mut "uart"));
+    ///
+    /// match drivers.try_insert(1, "spi") {
+    ///     Err(TryInsertError::Occupied { key, existing, .. }) => {
+    ///         assert_eq!((key, existing), (1, &"uart"));
+    ///     }
+    ///     _ => panic!("duplicate registration not detected"),
+    /// }
+    ///
+    /// drivers.try_insert(2, "i2c").unwrap();
+    /// assert_eq!(
+    ///     drivers.try_insert(3, "can"),
+    ///     Err(TryInsertError::Full { key: 3, value: "can" })
+    /// );
+    /// ```
+    pub fn try_insert(&mut self, key: K, value: V) -> Result<&mut V, TryInsertError<'_, K, V>>
+    where
+        K: Hash + Eq,
+    {
+        match self.map.find_or_hash(&key) {
+            Ok((_, b_idx)) => Err(TryInsertError::Occupied {
+                key,
+                value,
+                existing: &self.map.buckets[b_idx].value,
+            }),
+            Err(_) if self.map.buckets.is_full() => Err(TryInsertError::Full { key, value }),
+            Err(hash) => {
+                let _ = self.map.insert_hashed(key, value, hash);
+                Ok(&mut self.map.buckets.last_mut().unwrap().value)
+            }
+        }
+    }
+
+    /// Calls `f` for every key and a mutable reference to its value, in the order of the map,
+    /// until `f` returns an error.
+    ///
+    /// The first error is returned, the remaining values are left untouched. Values modified
+    /// before the error keep their modifications.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 100_u8).unwrap();
+    /// map.insert("b", 200_u8).unwrap();
+    ///
+    /// let result = map.try_modify_all(|k, v| {
+    ///     *v = v.checked_mul(2).ok_or(*k)?;
+    ///     Ok(())
+    /// });
+    /// assert_eq!(result, Err("b"));
+    /// assert_eq!(map.get(&"a"), Some(&200));
+    /// ```
+    pub fn try_modify_all<E, F>(&mut self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(&K, &mut V) -> Result<(), E>,
+    {
+        let buckets = self.map.buckets.as_mut_slice();
+        let mut idx = 0;
+        while idx < buckets.len() {
+            let bucket = &mut buckets[idx];
+            f(&bucket.key, &mut bucket.value)?;
+            idx += 1;
+        }
+        Ok(())
+    }
+
+    /// Applies `f` to the value of the key. Returns false if the key is not in the map.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut counters = FcHashMap::<u8, u32, 8>::new();
+    /// counters.insert(3, 0).unwrap();
+    ///
+    /// assert!(counters.update(&3, |c| *c += 1));
+    /// assert!(!counters.update(&4, |c| *c += 1));
+    /// assert_eq!(counters.get(&3), Some(&1));
+    /// ```
+    pub fn update<Q, F>(&mut self, key: &Q, f: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+        F: FnOnce(&mut V),
+    {
+        self.get_mut(key).map(f).is_some()
+    }
+
+    /// Return an iterator over the values of the map, in their order.
+    ///
+    /// The order matches the order of [`entries()`](FcHashMap::entries) and `keys()`.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 1).unwrap();
+    /// map.insert("b", 2).unwrap();
+    /// map.insert("c", 3).unwrap();
+    ///
+    /// let v: Vec<_> = map.values().collect();
+    /// assert_eq!(v, vec![&1, &2, &3]);
+    /// ```
+    pub fn values(&self) -> Values<'_, K, V> {
+        Values {
+            iter: self.map.buckets.iter(),
+        }
+    }
+
+    /// Return an iterator over mutable references to the the values of the map, in their order.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert("a", 1).unwrap();
+    /// map.insert("b", 2).unwrap();
+    /// map.insert("c", 3).unwrap();
+    ///
+    /// for val in map.values_mut() {
+    ///     *val += 10;
+    /// }
+    ///
+    /// let v: Vec<_> = map.values().collect();
+    /// assert_eq!(v, vec![&11, &12, &13]);
+    /// ```
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        ValuesMut {
+            iter: self.map.buckets.iter_mut(),
+        }
+    }
+
+    /// Returns the worst case numbers of steps of a single `get()`, `insert()` or `remove()` on a
+    /// map with `len` entries.
+    ///
+    /// The bounds hold for any keys, even if all of them have the same hash value. The search
+    /// for a key ends at the first empty place of the hash table, so it reads at most `len + 1`
+    /// places, or all places of a full map. `remove()` reads up to `len` more places to fix the
+    /// index of the entry that is moved into the removed place. Both `insert()` and `remove()`
+    /// move at most `len` places of the hash table. With well distributed keys and a load below
+    /// 80%, the typical numbers are far lower, see [Performance](FcHashMap#performance).
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::{FcHashMap, OpBounds};
+    ///
+    /// const BOUNDS: OpBounds = FcHashMap::<u32, u32, 64>::worst_case_ops(48);
+    /// assert_eq!(BOUNDS.probes, 97);
+    /// assert_eq!(BOUNDS.shifts, 48);
+    /// ```
+    pub const fn worst_case_ops(len: usize) -> OpBounds {
+        let len = if len < CAP { len } else { CAP };
+        let search = if len < CAP { len + 1 } else { CAP };
+        OpBounds {
+            probes: search + len,
+            key_comparisons: len,
+            shifts: len,
+        }
+    }
+}
+
+impl<K, T, const CAP: usize> FcHashMap<K, Cell<T>, CAP> {
+    /// Returns a copy of the content of the cell of the key.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use core::cell::Cell;
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 8>::new();
+    /// map.insert("rx", Cell::new(0_u32)).unwrap();
+    ///
+    /// // Only a shared reference is needed to change the values
+    /// let counters = &map;
+    /// counters.set_cell(&"rx", 5);
+    /// counters.update_cell(&"rx", |n| n + 1);
+    /// assert_eq!(counters.get_cell(&"rx"), Some(6));
+    /// assert_eq!(counters.get_cell(&"tx"), None);
+    /// ```
+    pub fn get_cell<Q>(&self, key: &Q) -> Option<T>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+        T: Copy,
+    {
+        self.get(key).map(Cell::get)
+    }
+
+    /// Sets the content of the cell of the key. Returns false if the key is not in the map.
+    pub fn set_cell<Q>(&self, key: &Q, value: T) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.get(key).map(|cell| cell.set(value)).is_some()
+    }
+
+    /// Replaces the content of the cell of the key with the result of `f`. Returns false if the
+    /// key is not in the map.
+    pub fn update_cell<Q, F>(&self, key: &Q, f: F) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+        T: Copy,
+        F: FnOnce(T) -> T,
+    {
+        self.get(key).map(|cell| cell.set(f(cell.get()))).is_some()
+    }
+}
+
+impl<A, B, V, const CAP: usize> FcHashMap<(A, B), V, CAP> {
+    /// Return an iterator over all entries whose composite key starts with `first`.
+    ///
+    /// This is a linear scan over the map, in its order. It spares the open coded loop for the
+    /// common "all streams of connection X" query on maps with tuple keys.
+    ///
+    /// The key may be any borrowed form of the first component of the key type, but `Eq` on the
+    /// borrowed form must match that of the component type.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// let mut map = FcHashMap::<_, _, 16>::new();
+    /// map.insert((1_u8, 10_u16), "a").unwrap();
+    /// map.insert((2_u8, 10_u16), "b").unwrap();
+    /// map.insert((1_u8, 20_u16), "c").unwrap();
+    ///
+    /// let v: Vec<_> = map.iter_matching(&1).map(|(_, v)| *v).collect();
+    /// assert_eq!(v, vec!["a", "c"]);
+    /// ```
+    pub fn iter_matching<'a, Q>(
+        &'a self,
+        first: &'a Q,
+    ) -> impl Iterator<Item = (&'a (A, B), &'a V)>
+    where
+        A: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        self.iter().filter(move |(key, _)| key.0.borrow() == first)
+    }
+}
+
+impl<A, B, V, const M: usize, const N: usize> FcHashMap<A, FcHashMap<B, V, M>, N> {
+    /// Returns a reference to the value of the key pair `(a, b)` in a map of maps.
+    ///
+    /// Nested maps model sparse two-dimensional keys: the outer map holds a map for every first
+    /// key that is in use. [`insert2()`](FcHashMap::insert2) creates the inner maps as needed
+    /// and [`remove2()`](FcHashMap::remove2) removes them when they become empty.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use fchashmap::FcHashMap;
+    ///
+    /// // Node -> (object index -> value)
+    /// let mut od = FcHashMap::<u8, FcHashMap<u16, u32, 8>, 4>::new();
+    /// od.insert2(1, 0x1000, 7).unwrap();
+    /// od.insert2(1, 0x1001, 8).unwrap();
+    /// od.insert2(2, 0x1000, 9).unwrap();
+    ///
+    /// assert_eq!(od.get2(&1, &0x1001), Some(&8));
+    /// assert_eq!(od.get2(&2, &0x1001), None);
+    /// assert_eq!(od.iter_flat().count(), 3);
+    ///
+    /// assert_eq!(od.remove2(&2, &0x1000), Some(9));
+    /// assert!(!od.contains_key(&2));
+    /// ```
+    pub fn get2<Q1, Q2>(&self, a: &Q1, b: &Q2) -> Option<&V>
+    where
+        A: Borrow<Q1>,
+        Q1: ?Sized + Hash + Eq,
+        B: Borrow<Q2>,
+        Q2: ?Sized + Hash + Eq,
+    {
+        self.get(a)?.get(b)
+    }
+
+    /// Returns a mutable reference to the value of the key pair `(a, b)` in a map of maps.
+    pub fn get2_mut<Q1, Q2>(&mut self, a: &Q1, b: &Q2) -> Option<&mut V>
+    where
+        A: Borrow<Q1>,
+        Q1: ?Sized + Hash + Eq,
+        B: Borrow<Q2>,
+        Q2: ?Sized + Hash + Eq,
+    {
+        self.get_mut(a)?.get_mut(b)
+    }
+
+    /// Inserts a value for the key pair `(a, b)` into a map of maps, and creates the inner map
+    /// of `a` if there is none yet.
+    ///
+    /// Returns the old value of the key pair, if there was one. If the outer map or the inner
+    /// map is full, the key pair and the value are given back.
+    pub fn insert2(&mut self, a: A, b: B, value: V) -> Result<Option<V>, (A, B, V)>
+    where
+        A: Hash + Eq,
+        B: Hash + Eq,
+    {
+        if let Some(inner) = self.get_mut(&a) {
+            return inner.insert(b, value).map_err(|(b, value)| (a, b, value));
+        }
+        if self.len() == N {
+            return Err((a, b, value));
+        }
+        let mut inner = FcHashMap::new();
+        if let Err((b, value)) = inner.insert(b, value) {
+            return Err((a, b, value));
+        }
+        let inserted = self.insert(a, inner).is_ok();
+        debug_assert!(inserted);
+        Ok(None)
+    }
+
+    /// Return an iterator over all key pairs and values of a map of maps, in the order of the
+    /// outer map and then of the inner maps.
+    pub fn iter_flat(&self) -> impl Iterator<Item = (&A, &B, &V)> {
+        self.iter()
+            .flat_map(|(a, inner)| inner.iter().map(move |(b, value)| (a, b, value)))
+    }
+
+    /// Removes the key pair `(a, b)` from a map of maps and returns its value, if it was in the
+    /// map. An inner map that becomes empty is removed from the outer map.
+    pub fn remove2<Q1, Q2>(&mut self, a: &Q1, b: &Q2) -> Option<V>
+    where
+        A: Borrow<Q1>,
+        Q1: ?Sized + Hash + Eq,
+        B: Borrow<Q2>,
+        Q2: ?Sized + Hash + Eq,
+    {
+        let inner = self.get_mut(a)?;
+        let value = inner.remove(b)?;
+        if inner.is_empty() {
+            self.remove(a);
+        }
+        Some(value)
+    }
+}
+
+impl<K, V, const CAP: usize> Default for FcHashMap<K, V, CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Implement Clone trait
+impl<K, V, const CAP: usize> Clone for FcHashMap<K, V, CAP>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            map: self.map.clone(),
+        }
+    }
+
+    fn clone_from(&mut self, source: &Self) {
+        self.map.clone_from(&source.map);
+    }
+}
+
+// Compare maps by their content, independent of the order of the entries and of the capacities
+impl<K, V, const CAP: usize, const CAP2: usize> PartialEq<FcHashMap<K, V, CAP2>>
+    for FcHashMap<K, V, CAP>
+where
+    K: Eq + Hash,
+    V: PartialEq,
+{
+    fn eq(&self, other: &FcHashMap<K, V, CAP2>) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K, V, const CAP: usize> Eq for FcHashMap<K, V, CAP>
+where
+    K: Eq + Hash,
+    V: Eq,
+{
+}
+
+// Enable possibility to extract debug informations. The alternate form "{:#?}" adds some
+// diagnostics about the fill level of the map. With the debug-truncate feature, only the first
+// DEBUG_LIMIT entries are shown.
+impl<K, V, const CAP: usize> fmt::Debug for FcHashMap<K, V, CAP>
+where
+    K: Eq + Hash + fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let entries = DebugEntries {
+            map: self,
+            limit: DEBUG_LIMIT,
+        };
+        if f.alternate() {
+            f.debug_struct("FcHashMap")
+                .field("len", &self.len())
+                .field("capacity", &CAP)
+                .field("load_percent", &self.load_percent())
+                .field("max_displacement", &self.map.max_displacement())
+                .field("entries", &entries)
+                .finish()
+        } else {
+            entries.fmt(f)
+        }
+    }
+}
+
+// Number of entries shown by the Debug output of a map
+#[cfg(feature = "debug-truncate")]
+const DEBUG_LIMIT: usize = 32;
+#[cfg(not(feature = "debug-truncate"))]
+const DEBUG_LIMIT: usize = usize::MAX;
+
+// Debug output of the entries of a map only, up to a limit. The omitted entries are summarized
+// in a last entry "..: <n> more".
+struct DebugEntries<'a, K, V, const CAP: usize> {
+    map: &'a FcHashMap<K, V, CAP>,
+    limit: usize,
+}
+
+impl<'a, K, V, const CAP: usize> fmt::Debug for DebugEntries<'a, K, V, CAP>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut map = f.debug_map();
+        map.entries(self.map.iter().take(self.limit));
+        let omitted = self.map.len().saturating_sub(self.limit);
+        if omitted > 0 {
+            map.entry(&format_args!(".."), &format_args!("{} more", omitted));
+        }
+        map.finish()
+    }
+}
+
+// Panic because a full map rejected the entry at the given position of an iterator, naming the
+// position with the panic-position feature in debug builds
+fn panic_full(position: usize, capacity: usize) -> ! {
+    #[cfg(all(feature = "panic-position", debug_assertions))]
+    panic!(
+        "FcHashMap is full ({}/{}), can't insert entry {} of the iterator",
+        capacity, capacity, position
+    );
+    #[cfg(not(all(feature = "panic-position", debug_assertions)))]
+    {
+        let _ = position;
+        panic!("FcHashMap is full ({}/{})", capacity, capacity)
+    }
+}
+
+// Extend map with data of another map, consuming input
+impl<K, V, const CAP: usize> Extend<(K, V)> for FcHashMap<K, V, CAP>
+where
+    K: Eq + Hash,
+{
     fn extend<I>(&mut self, iterable: I)
     where
         I: IntoIterator<Item = (K, V)>,
     {
-        for (k, v) in iterable {
-            self.insert(k, v).ok().unwrap();
+        for (position, (k, v)) in iterable.into_iter().enumerate() {
+            if self.insert(k, v).is_err() {
+                panic_full(position, CAP);
+            }
         }
     }
 }
@@ -461,8 +2925,33 @@ where
     }
 }
 
+// Build a map from a slice without panicking. Later duplicates replace the values of earlier ones.
+impl<K, V, const CAP: usize> TryFrom<&[(K, V)]> for FcHashMap<K, V, CAP>
+where
+    K: Eq + Hash + Clone,
+    V: Clone,
+{
+    type Error = FromSliceError;
+
+    fn try_from(pairs: &[(K, V)]) -> Result<Self, Self::Error> {
+        let mut map = FcHashMap::new();
+        for (index, (key, value)) in pairs.iter().enumerate() {
+            match map.entry(key.clone()) {
+                Ok(Entry::Occupied(mut entry)) => {
+                    entry.insert(value.clone());
+                }
+                Ok(Entry::Vacant(entry)) => {
+                    entry.insert(value.clone());
+                }
+                Err(_) => return Err(FromSliceError::Full { index }),
+            }
+        }
+        Ok(map)
+    }
+}
+
 // Indexing operation (container[index]) in immutable contexts
-impl<'a, K, Q, V, const CAP: usize> ops::Index<&'a Q> for FcHashMap<K, V, CAP>
+impl<K, Q, V, const CAP: usize> ops::Index<&Q> for FcHashMap<K, V, CAP>
 where
     K: Eq + Hash + Borrow<Q>,
     Q: ?Sized + Eq + Hash,
@@ -475,7 +2964,7 @@ where
 }
 
 // Indexing operations (container[index]) in mutable contexts
-impl<'a, K, Q, V, const N: usize> ops::IndexMut<&'a Q> for FcHashMap<K, V, N>
+impl<K, Q, V, const N: usize> ops::IndexMut<&Q> for FcHashMap<K, V, N>
 where
     K: Eq + Hash + Borrow<Q>,
     Q: ?Sized + Eq + Hash,
@@ -497,3 +2986,15 @@ where
         self.iter()
     }
 }
+
+// Enables possibility to move all entries out of the map with a "for .. in map" iterator
+impl<K, V, const CAP: usize> IntoIterator for FcHashMap<K, V, CAP> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V, CAP>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter {
+            iter: self.map.buckets.into_iter(),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::{FcHashMap, Iter, IterMut};
+use core::borrow::Borrow;
+use hash32::Hash;
+
+/// A map that holds at most `LIMIT` of its `CAP` entries.
+///
+/// The map becomes slow above a load of about 80%. With a limit below the capacity, the headroom
+/// is part of the type: inserts of new keys are rejected once `LIMIT` entries are in the map, and
+/// a limit above the capacity fails to compile.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::FcHashMapWithLimit;
+///
+/// let mut map = FcHashMapWithLimit::<u32, u32, 8, 6>::new();
+/// for i in 0..6 {
+///     map.insert(i, i).unwrap();
+/// }
+/// assert_eq!(map.insert(6, 6), Err((6, 6)));
+/// assert_eq!(map.insert(5, 50), Ok(Some(5)));
+/// assert_eq!(map.as_map().capacity(), 8);
+/// ```
+///
+/// ```compile_fail
+/// use fchashmap::FcHashMapWithLimit;
+///
+/// let map = FcHashMapWithLimit::<u32, u32, 8, 9>::new();
+/// ```
+pub struct FcHashMapWithLimit<K, V, const CAP: usize, const LIMIT: usize> {
+    map: FcHashMap<K, V, CAP>,
+}
+
+impl<K, V, const CAP: usize, const LIMIT: usize> FcHashMapWithLimit<K, V, CAP, LIMIT> {
+    // The limit is checked at compile time, when new() is instantiated
+    const LIMIT_FITS: () = assert!(LIMIT <= CAP, "the limit of FcHashMapWithLimit exceeds CAP");
+
+    /// Creates an empty map.
+    pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::LIMIT_FITS;
+        FcHashMapWithLimit {
+            map: FcHashMap::new(),
+        }
+    }
+
+    /// Returns the number of elements the map can hold, which is `LIMIT`.
+    pub fn capacity(&self) -> usize {
+        LIMIT
+    }
+
+    forward_to_map!();
+
+    /// Remove all key-value pairs in the map.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Inserts a key-value pair into the map, see [`FcHashMap::insert()`].
+    ///
+    /// A new key is rejected if the map holds `LIMIT` entries. The value of a key that is
+    /// already in the map is replaced in any case.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)>
+    where
+        K: Hash + Eq,
+    {
+        let map = &mut self.map.map;
+        match map.find_or_hash(&key) {
+            Ok((_, b_idx)) => Ok(Some(core::mem::replace(&mut map.buckets[b_idx].value, value))),
+            Err(_) if map.buckets.len() >= LIMIT => Err((key, value)),
+            Err(hash) => map.insert_hashed(key, value, hash),
+        }
+    }
+
+    /// Return an iterator over the key-value pairs of the map, in their order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.map.iter()
+    }
+
+    /// Return an iterator over the key-value pairs of the map with mutable references to the
+    /// values, in their order.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.map.iter_mut()
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously
+    /// in the map.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.remove(key)
+    }
+}
+
+impl<K, V, const CAP: usize, const LIMIT: usize> Default
+    for FcHashMapWithLimit<K, V, CAP, LIMIT>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,151 @@
+/// Generates a newtype around [`FcHashMap`](crate::FcHashMap) with a small, domain named API.
+///
+/// Application code often shall not see the full API of the map. The generated type exposes only
+/// `new()`, `insert()`, `lookup()`, `lookup_mut()`, `expire()`, `len()`, `is_empty()` and
+/// `capacity()`. Attributes (including doc comments) and a visibility can be put in front of the
+/// name of the type.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::fc_map_newtype;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq, Eq, hash32_derive::Hash32)]
+/// pub struct SessionId(u32);
+///
+/// #[derive(Debug)]
+/// pub struct Session {
+///     packets: u32,
+/// }
+///
+/// fc_map_newtype!(
+///     /// The open sessions of the server
+///     pub SessionTable, SessionId => Session, 256
+/// );
+///
+/// let mut sessions = SessionTable::new();
+/// sessions.insert(SessionId(7), Session { packets: 0 }).unwrap();
+/// sessions.lookup_mut(&SessionId(7)).unwrap().packets += 1;
+///
+/// assert_eq!(sessions.lookup(&SessionId(7)).unwrap().packets, 1);
+/// assert!(sessions.expire(&SessionId(7)).is_some());
+/// assert!(sessions.is_empty());
+/// ```
+#[macro_export]
+macro_rules! fc_map_newtype {
+    ($(#[$meta:meta])* $vis:vis $name:ident, $key:ty => $value:ty, $cap:expr $(,)?) => {
+        $(#[$meta])*
+        $vis struct $name($crate::FcHashMap<$key, $value, { $cap }>);
+
+        impl $name {
+            /// Creates an empty table.
+            pub fn new() -> Self {
+                $name($crate::FcHashMap::new())
+            }
+
+            /// Returns the number of entries the table can hold.
+            pub fn capacity(&self) -> usize {
+                self.0.capacity()
+            }
+
+            /// Removes an entry from the table, returning its value if it was in the table.
+            pub fn expire(&mut self, key: &$key) -> Option<$value> {
+                self.0.remove(key)
+            }
+
+            /// Inserts an entry into the table, see `FcHashMap::insert()`.
+            pub fn insert(
+                &mut self,
+                key: $key,
+                value: $value,
+            ) -> Result<Option<$value>, ($key, $value)> {
+                self.0.insert(key, value)
+            }
+
+            /// Returns true if the table contains no entries.
+            pub fn is_empty(&self) -> bool {
+                self.0.is_empty()
+            }
+
+            /// Returns the number of entries in the table.
+            pub fn len(&self) -> usize {
+                self.0.len()
+            }
+
+            /// Returns a reference to the value corresponding to the key.
+            pub fn lookup(&self, key: &$key) -> Option<&$value> {
+                self.0.get(key)
+            }
+
+            /// Returns a mutable reference to the value corresponding to the key.
+            pub fn lookup_mut(&mut self, key: &$key) -> Option<&mut $value> {
+                self.0.get_mut(key)
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    };
+}
+
+/// Declares a type alias for a [`FcHashMap`](crate::FcHashMap) that holds a given number of
+/// entries, optionally with a limit for its size in bytes.
+///
+/// Firmware for several boards often derives the sizes of its maps from a configuration constant
+/// per board. The number of entries may be any constant expression, it is rounded up to a valid
+/// capacity with [`capacity_for()`](crate::capacity_for). With `max_bytes`, the build fails if
+/// the map would take more memory than that. Attributes (including doc comments) and a
+/// visibility can be put in front of the name of the type.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::fc_map_type;
+///
+/// pub struct Board {
+///     max_nodes: usize,
+///     node_table_bytes: usize,
+/// }
+///
+/// pub const BOARD: Board = Board {
+///     max_nodes: 50,
+///     node_table_bytes: 2048,
+/// };
+///
+/// fc_map_type!(
+///     /// The nodes of the mesh network
+///     pub NodeTable, u16 => u32, BOARD.max_nodes, max_bytes = BOARD.node_table_bytes
+/// );
+///
+/// let mut nodes = NodeTable::new();
+/// nodes.insert(0x1234, 7).unwrap();
+/// assert_eq!(nodes.capacity(), 64);
+/// ```
+///
+/// A map that exceeds its budget fails to compile:
+///
+/// ```compile_fail
+/// use fchashmap::fc_map_type;
+///
+/// fc_map_type!(NodeTable, u16 => u32, 300, max_bytes = 1024);
+/// ```
+#[macro_export]
+macro_rules! fc_map_type {
+    (
+        $(#[$meta:meta])* $vis:vis $name:ident, $key:ty => $value:ty, $entries:expr
+        $(, max_bytes = $max_bytes:expr)? $(,)?
+    ) => {
+        $(#[$meta])*
+        $vis type $name = $crate::FcHashMap<$key, $value, { $crate::capacity_for($entries) }>;
+
+        $(
+            const _: () = assert!(
+                ::core::mem::size_of::<$name>() <= $max_bytes,
+                "the map exceeds its memory budget"
+            );
+        )?
+    };
+}
@@ -1,15 +1,42 @@
 #![allow(dead_code)]
 use arrayvec::ArrayVec;
-use core::{borrow::Borrow, mem, slice};
+#[cfg(feature = "stats")]
+use core::cell::Cell;
+use core::{borrow::Borrow, iter::FusedIterator, mem, ptr, slice};
 use hash32::{BuildHasher, BuildHasherDefault, FnvHasher, Hash, Hasher};
+use crate::bounds::ProbeStats;
 
-#[derive(Clone, Copy, PartialEq)]
-struct HashValue(u16);
+// Version of the memory layout with the stable-layout feature. It has to be incremented whenever
+// the layout of HashValue, HashIndex, Bucket or Map changes.
+#[cfg(feature = "stable-layout")]
+const LAYOUT_VERSION: u32 = 1;
+
+// The buckets are stored in an ArrayVec, which repr(C) of Map doesn't reach into. arrayvec 0.7
+// lays it out as this repr(C) struct: the length, followed by the array of buckets. The layout is
+// not promised by arrayvec, so a release with a different size or alignment fails to compile.
+#[cfg(feature = "stable-layout")]
+#[repr(C)]
+struct BucketsLayout<K, V, const CAP: usize> {
+    #[cfg(not(target_pointer_width = "16"))]
+    len: u32,
+    #[cfg(target_pointer_width = "16")]
+    len: u16,
+    buckets: [mem::MaybeUninit<Bucket<K, V>>; CAP],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "stable-layout", repr(transparent))]
+pub struct HashValue(u16);
 
 // There are up to 0x7fff (32767) elements allowed. The first bit of u16 is used to mark
-// a empty element
+// a empty element. HashValue::new() always clears this bit, so no key and no hash value can ever
+// be mistaken for an empty element.
 const HASH_VALUE_IS_EMPTY: HashValue = HashValue(0x8000);
 
+// In a bucket, the first bit of the hash value marks an entry whose removal is deferred. It never
+// gets into the hash table, as HashIndex::new() strips it.
+const HASH_VALUE_MARK: u16 = 0x8000;
+
 impl HashValue {
     // Create 15 bit hash value from u32 hash
     fn new(hash: u32) -> Self {
@@ -23,12 +50,34 @@ impl HashValue {
 
     // Calculate distance from expected index from current index
     fn h_idx_distance(&self, mask: usize, current_h_idx: usize) -> usize {
-        current_h_idx.wrapping_sub(self.desired_h_idx(mask) as usize) & mask
+        current_h_idx.wrapping_sub(self.desired_h_idx(mask)) & mask
+    }
+
+    // Check if the bucket with this hash value is marked for deferred removal
+    fn is_marked(&self) -> bool {
+        self.0 & HASH_VALUE_MARK != 0
+    }
+
+    // Calculate the tag of the hash value. The 15 bits are mixed, so that hash values in the same
+    // cluster get different tags although they share the bits of their desired index.
+    #[cfg(feature = "tags")]
+    fn tag(&self) -> u8 {
+        if *self == HASH_VALUE_IS_EMPTY {
+            TAG_EMPTY
+        } else {
+            (u32::from(self.0).wrapping_mul(0x9e37_79b1) >> 25) as u8
+        }
     }
 }
 
+// With the tags feature, a tag of 7 bits is kept for every place of the hash table. An empty
+// place has this tag, which no hash value can have.
+#[cfg(feature = "tags")]
+const TAG_EMPTY: u8 = 0x80;
+
 // A Combination of hash value and index into the bucket list
 #[derive(Clone, Copy)]
+#[cfg_attr(any(feature = "stable-layout", feature = "simd"), repr(C))]
 struct HashIndex {
     hash: HashValue,
     b_idx: u16,
@@ -37,7 +86,10 @@ struct HashIndex {
 impl HashIndex {
     // Create a nuew hash index from given parameters
     fn new(hash: HashValue, b_idx: usize) -> Self {
-        Self { hash, b_idx: b_idx as u16 }
+        Self {
+            hash: HashValue(hash.0 & !HASH_VALUE_MARK),
+            b_idx: b_idx as u16,
+        }
     }
 
     // Clear actual hash index an mark it as empty
@@ -51,32 +103,153 @@ impl HashIndex {
     }
 }
 
+// The layout of the hash table entries must never change silently with the stable-layout feature
+#[cfg(feature = "stable-layout")]
+const _: () = assert!(mem::size_of::<HashIndex>() == 4 && mem::align_of::<HashIndex>() == 2);
+
+// Number of hash table entries whose hash values are compared at once with the simd feature
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+const GROUP: usize = 8;
+
+// Compare the hash values of GROUP hash table entries with SSE2 instructions. Returns a bit mask
+// of the entries with the given hash value and a bit mask of the empty entries.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[inline(always)]
+fn match_group(group: &[HashIndex], hash: HashValue) -> (u32, u32) {
+    use core::arch::x86_64::{
+        __m128i, _mm_cmpeq_epi16, _mm_loadu_si128, _mm_movemask_epi8, _mm_set1_epi16,
+    };
+
+    assert!(group.len() == GROUP);
+    // unsafe is ok, SSE2 is part of x86_64 and the group covers two registers of 16 bytes, as
+    // HashIndex is repr(C) with two u16
+    let (matches, empty) = unsafe {
+        let ptr = group.as_ptr() as *const __m128i;
+        let (lo, hi) = (_mm_loadu_si128(ptr), _mm_loadu_si128(ptr.add(1)));
+        let byte_mask = |value: u16| {
+            let value = _mm_set1_epi16(value as i16);
+            let lo = _mm_movemask_epi8(_mm_cmpeq_epi16(lo, value)) as u32;
+            let hi = _mm_movemask_epi8(_mm_cmpeq_epi16(hi, value)) as u32;
+            lo | hi << 16
+        };
+        (byte_mask(hash.0), byte_mask(HASH_VALUE_IS_EMPTY.0))
+    };
+    (entry_mask(matches), entry_mask(empty))
+}
+
+// Reduce a mask with one bit per byte of GROUP hash table entries to one bit per entry. Only the
+// first byte of the hash value counts, the bytes of the bucket index may match by chance.
+#[cfg(all(feature = "simd", target_arch = "x86_64"))]
+#[inline(always)]
+fn entry_mask(byte_mask: u32) -> u32 {
+    (0..GROUP).fold(0, |mask, i| mask | (byte_mask >> (4 * i) & 1) << i)
+}
+
+// Check that a bucket index of the hash table points into the bucket list. With the hardening
+// feature this is checked in release builds as well, so a corrupted hash table leads to a
+// panic instead of a wrong lookup or an access out of bounds.
+#[inline(always)]
+fn check_b_idx(b_idx: usize, len: usize) {
+    #[cfg(feature = "hardening")]
+    assert!(b_idx < len, "FcHashMap is corrupted: bucket index out of bounds");
+    #[cfg(not(feature = "hardening"))]
+    debug_assert!(b_idx < len);
+}
+
 #[derive(Clone, Copy)]
+#[cfg_attr(feature = "stable-layout", repr(C))]
 pub struct Bucket<K, V> {
     pub key: K,
     pub value: V,
     hash: HashValue,
 }
 
+/// A lookup whose key is hashed, but not yet searched in the map.
+///
+/// Created by [`FcHashMap::begin_find()`](crate::FcHashMap::begin_find).
+#[derive(Clone, Copy, Debug)]
+pub struct PendingFind<'q, Q: ?Sized> {
+    key: &'q Q,
+    hash: HashValue,
+}
+
+#[cfg_attr(feature = "stable-layout", repr(C))]
 pub struct Map<K, V, const CAP: usize> {
     pub buckets: ArrayVec<Bucket<K, V>, CAP>,
     hash_table: [HashIndex; CAP],
+    #[cfg(feature = "tags")]
+    tags: [u8; CAP],
     build_hasher: BuildHasherDefault<FnvHasher>,
+    #[cfg(feature = "stats")]
+    comparisons: Cell<u32>,
 }
 
 impl<K, V, const CAP: usize> Map<K, V, CAP>
 {
+    // Signature of the memory layout, derived from the layout version, the sizes and alignments
+    // of keys and values, the capacity and the field offsets of the buckets
+    #[cfg(feature = "stable-layout")]
+    pub const LAYOUT_SIGNATURE: u32 = {
+        let items = [
+            LAYOUT_VERSION,
+            mem::size_of::<K>() as u32,
+            mem::align_of::<K>() as u32,
+            mem::size_of::<V>() as u32,
+            mem::align_of::<V>() as u32,
+            CAP as u32,
+            mem::offset_of!(Bucket<K, V>, key) as u32,
+            mem::offset_of!(Bucket<K, V>, value) as u32,
+            mem::offset_of!(Bucket<K, V>, hash) as u32,
+            mem::size_of::<Self>() as u32,
+        ];
+        let mut signature = 0;
+        let mut i = 0;
+        while i < items.len() {
+            signature = crate::key::combine(signature, items[i]);
+            i += 1;
+        }
+        signature
+    };
+
+    // The 15 bit hash values and the 16 bit bucket indices address at most 32768 entries. Larger
+    // capacities would silently truncate indices, so they are rejected at compile time.
+    const CAP_FITS_INDEX: () = assert!(CAP <= 0x8000, "the capacity of FcHashMap is limited to 32768");
+
+    // The hash table is indexed by masking the hash values, which needs a power of 2. Any other
+    // capacity would leave places unused or address places beyond the table.
+    const CAP_IS_POWER_OF_TWO: () =
+        assert!(CAP.is_power_of_two(), "the capacity of FcHashMap must be a power of 2");
+
+    // The bucket storage has to match BucketsLayout for the layout signature to hold
+    #[cfg(feature = "stable-layout")]
+    const BUCKETS_MATCH_LAYOUT: () = assert!(
+        mem::size_of::<ArrayVec<Bucket<K, V>, CAP>>()
+            == mem::size_of::<BucketsLayout<K, V, CAP>>()
+            && mem::align_of::<ArrayVec<Bucket<K, V>, CAP>>()
+                == mem::align_of::<BucketsLayout<K, V, CAP>>(),
+        "the layout of ArrayVec differs from the one of arrayvec 0.7"
+    );
+
     // Create a new map
-    pub fn new() -> Self {
-        debug_assert!((Self::capacity() as u32) < u32::MAX);
-        debug_assert!(Self::capacity().count_ones() == 1);
+    pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CAP_FITS_INDEX;
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CAP_IS_POWER_OF_TWO;
+        #[cfg(feature = "stable-layout")]
+        #[allow(clippy::let_unit_value)]
+        let () = Self::BUCKETS_MATCH_LAYOUT;
         Map {
-            buckets: ArrayVec::new(),
+            buckets: ArrayVec::new_const(),
             hash_table: [HashIndex {
                 hash: HASH_VALUE_IS_EMPTY,
                 b_idx: 0,
             }; CAP],
+            #[cfg(feature = "tags")]
+            tags: [TAG_EMPTY; CAP],
             build_hasher: BuildHasherDefault::new(),
+            #[cfg(feature = "stats")]
+            comparisons: Cell::new(0),
         }
     }
 
@@ -101,6 +274,52 @@ impl<K, V, const CAP: usize> Map<K, V, CAP>
         HashValue::new(h.finish())
     }
 
+    // Check whether the keys spread well over the hash table. This is done at a few fill levels
+    // only, up to a load factor of 50%. Up to there, Robin Hood hashing with well distributed keys
+    // has a mean displacement far below 1.
+    #[cfg(all(feature = "key-diagnostics", debug_assertions))]
+    fn check_key_distribution(&self) {
+        let len = self.buckets.len();
+        if len < 64 || !len.is_power_of_two() || len > Self::capacity() / 2 {
+            return;
+        }
+        let displacement: usize = self
+            .hash_table
+            .iter()
+            .enumerate()
+            .filter(|(_, hash_index)| !hash_index.is_empty())
+            .map(|(h_idx, hash_index)| hash_index.hash.h_idx_distance(Self::mask(), h_idx))
+            .sum();
+        debug_assert!(
+            displacement <= 4 * len,
+            "fchashmap: keys are badly distributed (mean displacement {} at {} entries), \
+             consider fchashmap::key::Mixed or a better Hash implementation",
+            displacement / len,
+            len
+        );
+    }
+
+    // Count a full key comparison with the stats feature. Returns always true, so that it can be
+    // chained right in front of the comparison.
+    #[inline(always)]
+    fn count_comparison(&self) -> bool {
+        #[cfg(feature = "stats")]
+        self.comparisons.set(self.comparisons.get().wrapping_add(1));
+        true
+    }
+
+    // Number of full key comparisons since creation or the last reset
+    #[cfg(feature = "stats")]
+    pub fn comparisons(&self) -> u32 {
+        self.comparisons.get()
+    }
+
+    // Reset the number of full key comparisons
+    #[cfg(feature = "stats")]
+    pub fn reset_comparisons(&self) {
+        self.comparisons.set(0);
+    }
+
     // Inserts a key-value pair into the map.
     pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)>
     where
@@ -109,8 +328,19 @@ impl<K, V, const CAP: usize> Map<K, V, CAP>
         if self.buckets.is_full() {
             return Err((key, value));
         }
-
         let hash = self.hash_with(&key);
+        self.insert_hashed(key, value, hash)
+    }
+
+    // Inserts a key-value pair with an already calculated hash value into a map that is not full
+    pub fn insert_hashed(&mut self, key: K, value: V, hash: HashValue) -> Result<Option<V>, (K, V)>
+    where
+        K: PartialEq,
+    {
+        debug_assert!(!self.buckets.is_full());
+        #[cfg(all(feature = "key-diagnostics", debug_assertions))]
+        self.check_key_distribution();
+
         let mut h_idx = hash.desired_h_idx(Self::mask());
         let mut h_idx_dist = 0;
 
@@ -121,12 +351,13 @@ impl<K, V, const CAP: usize> Map<K, V, CAP>
             if hash_index.is_empty() {
                 // Case 1: empty hash index found, insert data and return None
                 *hash_index = HashIndex::new(hash, self.buckets.len());
+                self.sync_tag(h_idx);
                 // unsafe is ok, we already checked that we aren't exceeding the capacity
                 unsafe { self.buckets.push_unchecked(Bucket { key, value, hash }) }
                 return Ok(None);
             } else {
                 let b_idx = hash_index.b_idx as usize;
-                debug_assert!(b_idx < self.buckets.len());
+                check_b_idx(b_idx, self.buckets.len());
                 let their_h_idx_dist = hash_index.hash.h_idx_distance(Self::mask(), h_idx);
                 if their_h_idx_dist < h_idx_dist {
                     // Case 2: a place in the hash_table has been found that is suitable. There
@@ -142,16 +373,19 @@ impl<K, V, const CAP: usize> Map<K, V, CAP>
                         if next_hash_index.is_empty() {
                             // We found the right place: store and return
                             *next_hash_index = hash_index;
+                            self.sync_tag(h_idx);
                             unsafe { self.buckets.push_unchecked( Bucket { key, value, hash }) }
                             return Ok(None);
                         } else {
                             // Replace HashIndexs and continue shifting and searching for a vacancy
                             hash_index = mem::replace(next_hash_index, hash_index);
+                            self.sync_tag(h_idx);
                         }
                         h_idx += 1;
                         h_idx &= Self::mask();
                     }
                 } else if hash_index.hash == hash
+                    && self.count_comparison()
                     && unsafe { self.buckets.get_unchecked(b_idx).key == key }
                 {
                     // Case 3: There was already an entry for this key. We leave the place in the
@@ -175,41 +409,227 @@ impl<K, V, const CAP: usize> Map<K, V, CAP>
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
-        if self.buckets.len() == 0 {
-            return None;
+        self.find_hashed(key, self.hash_with(key))
+    }
+
+    // Find a key whose 32 bit hash value is given instead of calculated, e.g. to replay a log
+    pub fn find_with_hash(&self, key: &K, hash: u32) -> Option<(usize, usize)>
+    where
+        K: Hash + Eq,
+    {
+        self.find_hashed(key, HashValue::new(hash))
+    }
+
+    // Insert a key whose 32 bit hash value is given instead of calculated, e.g. to replay a log
+    pub fn insert_with_hash(&mut self, key: K, value: V, hash: u32) -> Result<Option<V>, (K, V)>
+    where
+        K: Eq,
+    {
+        if self.buckets.is_full() {
+            return Err((key, value));
         }
+        self.insert_hashed(key, value, HashValue::new(hash))
+    }
 
+    // Find a key in the map, or return its hash value if it is not there
+    pub fn find_or_hash(&self, key: &K) -> Result<(usize, usize), HashValue>
+    where
+        K: Hash + Eq,
+    {
         let hash = self.hash_with(key);
+        self.find_hashed(key, hash).ok_or(hash)
+    }
+
+    // Find a key with an already calculated hash value
+    pub fn find_hashed<Q>(&self, key: &Q, hash: HashValue) -> Option<(usize, usize)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        if self.buckets.is_empty() {
+            return None;
+        }
+
+        #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+        if CAP >= GROUP {
+            return self.find_grouped(key, hash);
+        }
+
         let mut h_idx = hash.desired_h_idx(Self::mask());
-        let mut h_idx_dist: usize = 0;
+        #[cfg(feature = "tags")]
+        let tag = hash.tag();
 
-        loop {
-            let hash_index = &self.hash_table[h_idx];
-            if hash_index.is_empty() {
+        // give up after full table scan (wrap arround)
+        for _ in 0..Self::capacity() {
+            // With the tags feature, only the places with a matching tag are read from the hash
+            // table, so a miss reads a single byte per place
+            #[cfg(feature = "tags")]
+            match self.tags[h_idx] {
+                TAG_EMPTY => return None,
+                t if t == tag => {
+                    if let Some(found) = self.compare_at(key, hash, h_idx) {
+                        return Some(found);
+                    }
+                }
+                _ => {}
+            }
+            #[cfg(not(feature = "tags"))]
+            if self.hash_table[h_idx].is_empty() {
                 return None;
-            } else {
-                let b_idx = hash_index.b_idx as usize;
-                debug_assert!(b_idx < self.buckets.len());
+            } else if let Some(found) = self.compare_at(key, hash, h_idx) {
+                return Some(found);
+            }
+            h_idx += 1;
+            h_idx &= Self::mask();
+        }
+        None
+    }
 
-                if h_idx_dist > hash.h_idx_distance(Self::mask(), h_idx) {
-                    // give up after full table scan (wrap arround)
+    // Find a key with an already calculated hash value, comparing the hash values of GROUP hash
+    // table entries at once. Only the entries in front of the first empty one are candidates, so
+    // the keys are compared exactly as often as in the scalar search. Groups that would wrap
+    // around the end of the hash table are searched entry by entry.
+    #[cfg(all(feature = "simd", target_arch = "x86_64"))]
+    fn find_grouped<Q>(&self, key: &Q, hash: HashValue) -> Option<(usize, usize)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let mut h_idx = hash.desired_h_idx(Self::mask());
+        let mut scanned = 0;
+
+        while scanned < CAP {
+            if h_idx + GROUP <= CAP {
+                let (matches, empty) = match_group(&self.hash_table[h_idx..h_idx + GROUP], hash);
+                // Limit the group to the entries up to the first empty one and the entries that
+                // were not scanned yet
+                let remaining = (CAP - scanned).min(GROUP);
+                let end = (empty.trailing_zeros() as usize).min(remaining);
+                let mut candidates = matches & ((1 << end) - 1);
+                while candidates != 0 {
+                    let i = candidates.trailing_zeros() as usize;
+                    candidates &= candidates - 1;
+                    if let Some(found) = self.compare_at(key, hash, h_idx + i) {
+                        return Some(found);
+                    }
+                }
+                if end < remaining {
                     return None;
-                } else if hash == hash_index.hash && // unsafe is ok, because we checked the idx
-                    unsafe { self.buckets.get_unchecked(b_idx).key.borrow() == key }
-                {
-                    return Some((h_idx, b_idx));
                 }
+                h_idx = (h_idx + GROUP) & Self::mask();
+                scanned += GROUP;
+            } else {
+                if self.hash_table[h_idx].is_empty() {
+                    return None;
+                } else if let Some(found) = self.compare_at(key, hash, h_idx) {
+                    return Some(found);
+                }
+                h_idx = (h_idx + 1) & Self::mask();
+                scanned += 1;
             }
-            h_idx_dist += 1;
-            h_idx += 1;
-            h_idx &= Self::mask();
+        }
+        None
+    }
+
+    // Compare the key with the entry at h_idx of the hash table, which must not be empty
+    #[inline(always)]
+    fn compare_at<Q>(&self, key: &Q, hash: HashValue, h_idx: usize) -> Option<(usize, usize)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Eq,
+    {
+        let hash_index = &self.hash_table[h_idx];
+        let b_idx = hash_index.b_idx as usize;
+        check_b_idx(b_idx, self.buckets.len());
+
+        if hash == hash_index.hash && self.count_comparison() &&
+            // unsafe is ok, because we checked the idx
+            unsafe { self.buckets.get_unchecked(b_idx).key.borrow() == key }
+        {
+            #[cfg(feature = "hardening")]
+            assert!(
+                self.buckets[b_idx].hash.0 & !HASH_VALUE_MARK == hash.0,
+                "FcHashMap is corrupted: hash table and buckets disagree"
+            );
+            Some((h_idx, b_idx))
+        } else {
+            None
+        }
+    }
+
+    // Transform all values, keeping the keys, their hash values and the hash table
+    pub fn map_values<U, F>(self, mut f: F) -> Map<K, U, CAP>
+    where
+        F: FnMut(&K, V) -> U,
+    {
+        Map {
+            buckets: self
+                .buckets
+                .into_iter()
+                .map(|Bucket { key, value, hash }| {
+                    let value = f(&key, value);
+                    Bucket { key, value, hash }
+                })
+                .collect(),
+            hash_table: self.hash_table,
+            #[cfg(feature = "tags")]
+            tags: self.tags,
+            build_hasher: self.build_hasher,
+            #[cfg(feature = "stats")]
+            comparisons: self.comparisons,
         }
     }
 
+    // Like map_values(), but the map is kept and the keys are cloned
+    pub fn map_values_ref<U, F>(&self, mut f: F) -> Map<K, U, CAP>
+    where
+        K: Clone,
+        F: FnMut(&K, &V) -> U,
+    {
+        Map {
+            buckets: self
+                .buckets
+                .iter()
+                .map(|bucket| Bucket {
+                    key: bucket.key.clone(),
+                    value: f(&bucket.key, &bucket.value),
+                    hash: bucket.hash,
+                })
+                .collect(),
+            hash_table: self.hash_table,
+            #[cfg(feature = "tags")]
+            tags: self.tags,
+            build_hasher: self.build_hasher.clone(),
+            #[cfg(feature = "stats")]
+            comparisons: self.comparisons.clone(),
+        }
+    }
+
+    // Start a lookup by calculating the hash value of the key
+    pub fn begin_find<'q, Q>(&self, key: &'q Q) -> PendingFind<'q, Q>
+    where
+        Q: ?Sized + Hash,
+    {
+        PendingFind {
+            key,
+            hash: self.hash_with(key),
+        }
+    }
+
+    // Finish a lookup started with begin_find()
+    pub fn complete_find<Q>(&self, pending: PendingFind<'_, Q>) -> Option<(usize, usize)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.find_hashed(pending.key, pending.hash)
+    }
+
     // Delete a found key value pair
-    fn remove_found(&mut self, found_h_idx: usize, found_b_idx: usize) -> (K, V) {
+    pub fn remove_found(&mut self, found_h_idx: usize, found_b_idx: usize) -> (K, V) {
         // The HashIndex at location h_idx and the bucket at location b_idx are deleted.
         self.hash_table[found_h_idx].clear();
+        self.sync_tag(found_h_idx);
         let deleted_bucket = self.buckets.swap_pop(found_b_idx).unwrap(); // ArrayVec;
         //let deleted_bucket = unsafe { self.buckets.swap_remove_unchecked(found_b_idx) }; // heapless::Vec;
 
@@ -228,8 +648,32 @@ impl<K, V, const CAP: usize> Map<K, V, CAP>
             }
         }
 
-        // Now a backward shift deletion is performed to close the gap in the hash_table created
-        // by the removal.
+        self.close_gap(found_h_idx);
+        (deleted_bucket.key, deleted_bucket.value)
+    }
+
+    // Removes an entry like remove_found(), but shifts all following buckets one place down
+    // instead of moving the last bucket into the gap. This keeps the order of the buckets and
+    // costs a pass over the whole hash table.
+    pub fn shift_remove_found(&mut self, found_h_idx: usize, found_b_idx: usize) -> (K, V) {
+        self.hash_table[found_h_idx].clear();
+        self.sync_tag(found_h_idx);
+        let deleted_bucket = self.buckets.remove(found_b_idx);
+
+        // Every index behind the removed bucket moves one place down
+        for hash_index in self.hash_table.iter_mut() {
+            if !hash_index.is_empty() && hash_index.b_idx as usize > found_b_idx {
+                hash_index.b_idx -= 1;
+            }
+        }
+
+        self.close_gap(found_h_idx);
+        (deleted_bucket.key, deleted_bucket.value)
+    }
+
+    // Close the gap at the cleared location h_idx of the hash_table with a backward shift
+    // deletion.
+    fn close_gap(&mut self, found_h_idx: usize) {
         let mut h_idx = found_h_idx;
         loop {
             let last_h_idx = h_idx;
@@ -246,12 +690,300 @@ impl<K, V, const CAP: usize> Map<K, V, CAP>
                     unsafe { *self.hash_table.get_unchecked_mut(last_h_idx) = hash_index }
                     // clear the moved hash_index entry
                     self.hash_table[h_idx].clear();
+                    self.sync_tag(last_h_idx);
+                    self.sync_tag(h_idx);
                 } else {
                     break;
                 }
             }
         }
-        (deleted_bucket.key, deleted_bucket.value)
+    }
+
+    // Append a bucket without touching the hash table. The hash table has to be rebuilt with
+    // build_sorted_hash_table() before the map is used again.
+    pub fn push_unindexed(&mut self, key: K, value: V) -> Result<(), (K, V)>
+    where
+        K: Hash,
+    {
+        let hash = self.hash_with(&key);
+        self.buckets
+            .try_push(Bucket { key, value, hash })
+            .map_err(|e| {
+                let bucket = e.element();
+                (bucket.key, bucket.value)
+            })
+    }
+
+    // Sort the buckets by their desired index and lay out the hash table in one pass. As the
+    // HashIndices are placed in the order of their desired index, every cluster is already in the
+    // order that Robin Hood hashing would have produced, but without any probing or shifting.
+    pub fn build_sorted_hash_table(&mut self) {
+        let mask = Self::mask();
+        self.buckets
+            .sort_unstable_by_key(|bucket| bucket.hash.desired_h_idx(mask));
+
+        // A cluster at the end of the hash table wraps around to its beginning. Find out how many
+        // places at the beginning are taken by the wrapped entries. The number can only grow with
+        // each round, so this ends after a few rounds at most.
+        let mut carry = 0;
+        loop {
+            let mut next_h_idx = carry;
+            for bucket in self.buckets.iter() {
+                next_h_idx = next_h_idx.max(bucket.hash.desired_h_idx(mask)) + 1;
+            }
+            let new_carry = next_h_idx.saturating_sub(Self::capacity());
+            if new_carry == carry {
+                break;
+            }
+            carry = new_carry;
+        }
+
+        for hash_index in self.hash_table.iter_mut() {
+            hash_index.clear();
+        }
+        let mut next_h_idx = carry;
+        for (b_idx, bucket) in self.buckets.iter().enumerate() {
+            let h_idx = next_h_idx.max(bucket.hash.desired_h_idx(mask));
+            self.hash_table[h_idx & mask] = HashIndex::new(bucket.hash, b_idx);
+            next_h_idx = h_idx + 1;
+        }
+        self.sync_tags();
+    }
+
+    // Exchange the entries with another map, which must be able to hold them. The buckets are
+    // swapped in place and the rest of the longer map is moved over, so the entries keep their
+    // order. The hash values don't depend on the capacity, so only the hash tables are rebuilt.
+    pub fn exchange<const CAP2: usize>(&mut self, other: &mut Map<K, V, CAP2>) {
+        let common = self.buckets.len().min(other.buckets.len());
+        for (bucket, other_bucket) in self.buckets[..common]
+            .iter_mut()
+            .zip(other.buckets[..common].iter_mut())
+        {
+            mem::swap(bucket, other_bucket);
+        }
+        if self.buckets.len() > common {
+            for bucket in self.buckets.drain(common..) {
+                other.buckets.push(bucket);
+            }
+        } else {
+            for bucket in other.buckets.drain(common..) {
+                self.buckets.push(bucket);
+            }
+        }
+        self.reindex();
+        other.reindex();
+    }
+
+    // Lay out the hash table for the buckets in their current order. Every HashIndex is placed
+    // with Robin Hood hashing, without touching the buckets.
+    pub fn reindex(&mut self) {
+        let mask = Self::mask();
+        for hash_index in self.hash_table.iter_mut() {
+            hash_index.clear();
+        }
+        for (b_idx, bucket) in self.buckets.iter().enumerate() {
+            let mut hash_index = HashIndex::new(bucket.hash, b_idx);
+            let mut h_idx = bucket.hash.desired_h_idx(mask);
+            let mut dist = 0;
+            loop {
+                let place = &mut self.hash_table[h_idx];
+                if place.is_empty() {
+                    *place = hash_index;
+                    break;
+                }
+                // Take the place of an entry that is closer to its desired place
+                let place_dist = place.hash.h_idx_distance(mask, h_idx);
+                if place_dist < dist {
+                    hash_index = mem::replace(place, hash_index);
+                    dist = place_dist;
+                }
+                h_idx = (h_idx + 1) & mask;
+                dist += 1;
+            }
+        }
+        self.sync_tags();
+    }
+
+    // Update the tag of the place h_idx after its HashIndex has changed
+    #[inline(always)]
+    fn sync_tag(&mut self, h_idx: usize) {
+        #[cfg(feature = "tags")]
+        {
+            self.tags[h_idx] = self.hash_table[h_idx].hash.tag();
+        }
+        #[cfg(not(feature = "tags"))]
+        let _ = h_idx;
+    }
+
+    // Update the tags of all places after the hash table has been rebuilt
+    fn sync_tags(&mut self) {
+        for h_idx in 0..Self::capacity() {
+            self.sync_tag(h_idx);
+        }
+    }
+
+    // Read every entry of the hash table and every bucket, so that they are loaded into the cache
+    pub fn prewarm(&self) {
+        for hash_index in self.hash_table.iter() {
+            // unsafe is ok, the reference is valid and HashIndex is Copy
+            unsafe { ptr::read_volatile(hash_index) };
+        }
+        #[cfg(feature = "tags")]
+        for tag in self.tags.iter() {
+            // unsafe is ok, the reference is valid and u8 is Copy
+            unsafe { ptr::read_volatile(tag) };
+        }
+        for bucket in self.buckets.iter() {
+            // unsafe is ok, the reference is valid and HashValue is Copy
+            unsafe { ptr::read_volatile(&bucket.hash) };
+        }
+    }
+
+    // Find the place in the hash table of the HashIndex that points to the bucket b_idx
+    pub fn h_idx_of(&self, b_idx: usize) -> usize {
+        let mut h_idx = self.buckets[b_idx].hash.desired_h_idx(Self::mask());
+        loop {
+            let hash_index = &self.hash_table[h_idx];
+            if !hash_index.is_empty() && hash_index.b_idx as usize == b_idx {
+                return h_idx;
+            }
+            h_idx += 1;
+            h_idx &= Self::mask();
+        }
+    }
+
+    // Mark the bucket b_idx for a deferred removal
+    pub fn mark(&mut self, b_idx: usize) {
+        self.buckets[b_idx].hash.0 |= HASH_VALUE_MARK;
+    }
+
+    // Remove all marked buckets and return their number
+    pub fn remove_marked(&mut self) -> usize {
+        self.remove_where(|bucket| bucket.hash.is_marked())
+    }
+
+    // Keep only the key value pairs for which f returns true
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.remove_where(|bucket| !f(&bucket.key, &mut bucket.value));
+    }
+
+    // Keep only the key value pairs for which f returns true, without changing the order of the
+    // buckets
+    pub fn retain_ordered<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        let len = self.buckets.len();
+        let guard = ReindexOnUnwind { map: self };
+        guard
+            .map
+            .buckets
+            .retain(|bucket| f(&bucket.key, &mut bucket.value));
+        mem::forget(guard);
+        if self.buckets.len() != len {
+            self.reindex();
+        }
+    }
+
+    // Remove all buckets for which f returns true and return their number
+    fn remove_where<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(&mut Bucket<K, V>) -> bool,
+    {
+        let mut removed = 0;
+        self.visit_rev(|map, b_idx| {
+            if f(&mut map.buckets[b_idx]) {
+                let h_idx = map.h_idx_of(b_idx);
+                map.remove_found(h_idx, b_idx);
+                removed += 1;
+            }
+        });
+        removed
+    }
+
+    // Return the hash value of the bucket b_idx, without the mark of a deferred removal. All maps
+    // hash the same way, so it is valid in any other map as well.
+    pub fn hash_of(&self, b_idx: usize) -> HashValue {
+        HashValue(self.buckets[b_idx].hash.0 & !HASH_VALUE_MARK)
+    }
+
+    // Swap two buckets and correct the HashIndices pointing to them
+    pub fn swap_buckets(&mut self, b_idx1: usize, b_idx2: usize) {
+        if b_idx1 == b_idx2 {
+            return;
+        }
+        let h_idx1 = self.h_idx_of(b_idx1);
+        let h_idx2 = self.h_idx_of(b_idx2);
+        self.buckets.swap(b_idx1, b_idx2);
+        self.hash_table[h_idx1].b_idx = b_idx2 as u16;
+        self.hash_table[h_idx2].b_idx = b_idx1 as u16;
+    }
+
+    // Call f with the map and the index of every bucket, which f may remove with remove_found()
+    // or swap with a visited bucket. The buckets are visited from the back, so the bucket that
+    // swap_pop() moves into a removed place has always been visited already.
+    pub fn visit_rev<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Self, usize),
+    {
+        for b_idx in (0..self.buckets.len()).rev() {
+            f(self, b_idx);
+        }
+    }
+
+    // Return the largest distance of a HashIndex from its desired place in the hash table
+    pub fn max_displacement(&self) -> usize {
+        self.hash_table
+            .iter()
+            .enumerate()
+            .filter(|(_, hash_index)| !hash_index.is_empty())
+            .map(|(h_idx, hash_index)| hash_index.hash.h_idx_distance(Self::mask(), h_idx))
+            .max()
+            .unwrap_or(0)
+    }
+
+    // Count the places of the hash table read by lookups of all keys in the map, and by lookups
+    // of missing keys starting at every place
+    pub fn probe_stats(&self) -> ProbeStats {
+        let mask = Self::mask();
+        let mut stats = ProbeStats {
+            len: self.buckets.len(),
+            capacity: Self::capacity(),
+            hit_probes: 0,
+            max_hit_probes: 0,
+            miss_probes: 0,
+            max_miss_probes: 0,
+        };
+        for (h_idx, hash_index) in self.hash_table.iter().enumerate() {
+            if !hash_index.is_empty() {
+                let probes = hash_index.hash.h_idx_distance(mask, h_idx) + 1;
+                stats.hit_probes += probes;
+                stats.max_hit_probes = stats.max_hit_probes.max(probes);
+            }
+        }
+
+        // A search for a missing key reads all places up to the next empty one. Walking backwards
+        // from an empty place, the number grows by one per place. Without an empty place, the
+        // search reads the whole table.
+        match self.hash_table.iter().position(|hash_index| hash_index.is_empty()) {
+            Some(empty_h_idx) => {
+                let mut probes = 0;
+                for step in 0..Self::capacity() {
+                    let h_idx = empty_h_idx.wrapping_sub(step) & mask;
+                    probes = if self.hash_table[h_idx].is_empty() { 1 } else { probes + 1 };
+                    stats.miss_probes += probes;
+                    stats.max_miss_probes = stats.max_miss_probes.max(probes);
+                }
+            }
+            None => {
+                stats.miss_probes = Self::capacity() * Self::capacity();
+                stats.max_miss_probes = Self::capacity();
+            }
+        }
+        stats
     }
 
     // Delete all keys and values of the map
@@ -260,6 +992,22 @@ impl<K, V, const CAP: usize> Map<K, V, CAP>
         for hash_index in self.hash_table.iter_mut() {
             hash_index.clear();
         }
+        self.sync_tags();
+    }
+
+    // Hand all keys and values to f in the order of the buckets. The hash table is cleared
+    // first, so the map stays consistent if f panics; the remaining entries are dropped then.
+    pub fn deinit<F>(&mut self, mut f: F)
+    where
+        F: FnMut(K, V),
+    {
+        for hash_index in self.hash_table.iter_mut() {
+            hash_index.clear();
+        }
+        self.sync_tags();
+        for bucket in self.buckets.drain(..) {
+            f(bucket.key, bucket.value);
+        }
     }
 
     // Returns a reference to the value corresponding to the key.
@@ -288,12 +1036,35 @@ impl<K, V, const CAP: usize> Map<K, V, CAP>
 
     // Remove key and coresponding value from the map
     pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.remove_entry(key).map(|(_, value)| value)
+    }
+
+    // Remove a key value pair and return both
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
     where
         K: Borrow<Q>,
         Q: ?Sized + Hash + Eq,
     {
         self.find(key)
-            .map(|(h_idx, b_idx)| self.remove_found(h_idx, b_idx).1)
+            .map(|(h_idx, b_idx)| self.remove_found(h_idx, b_idx))
+    }
+}
+
+// Rebuilds the hash table of the map when it is dropped. It guards code that moves or removes
+// buckets with a user closure, like a predicate or a comparison, before the hash table is rebuilt.
+// If the closure panics, the hash table must not keep pointing to the old places of the buckets,
+// as lookups read the buckets unchecked. On success the guard is forgotten.
+pub struct ReindexOnUnwind<'a, K, V, const CAP: usize> {
+    pub map: &'a mut Map<K, V, CAP>,
+}
+
+impl<'a, K, V, const CAP: usize> Drop for ReindexOnUnwind<'a, K, V, CAP> {
+    fn drop(&mut self) {
+        self.map.reindex();
     }
 }
 
@@ -306,14 +1077,52 @@ where
     fn clone(&self) -> Self {
         Self {
             buckets: self.buckets.clone(),
-            hash_table: self.hash_table.clone(),
+            hash_table: self.hash_table,
+            #[cfg(feature = "tags")]
+            tags: self.tags,
+            build_hasher: self.build_hasher.clone(),
+            #[cfg(feature = "stats")]
+            comparisons: self.comparisons.clone(),
+        }
+    }
+
+    // Copy the buckets and the hash table in place, without a temporary map
+    fn clone_from(&mut self, source: &Self) {
+        self.buckets.clone_from(&source.buckets);
+        self.hash_table.copy_from_slice(&source.hash_table);
+        #[cfg(feature = "tags")]
+        self.tags.copy_from_slice(&source.tags);
+        self.build_hasher.clone_from(&source.build_hasher);
+        #[cfg(feature = "stats")]
+        self.comparisons.set(source.comparisons.get());
+    }
+}
+
+impl<K: Copy, V: Copy, const CAP: usize> Map<K, V, CAP> {
+    // Clone the map with a raw copy of the buckets instead of cloning them one by one
+    pub fn fast_clone(&self) -> Self {
+        let mut buckets = ArrayVec::new();
+        // The buckets fit, as they come from a map of the same capacity
+        let copied = buckets.try_extend_from_slice(&self.buckets).is_ok();
+        debug_assert!(copied);
+        Self {
+            buckets,
+            hash_table: self.hash_table,
+            #[cfg(feature = "tags")]
+            tags: self.tags,
             build_hasher: self.build_hasher.clone(),
+            #[cfg(feature = "stats")]
+            comparisons: self.comparisons.clone(),
         }
     }
 }
 
+/// An iterator over the key-value pairs of a map, in the order of the map.
+///
+/// Created by [`FcHashMap::iter()`](crate::FcHashMap::iter).
+#[derive(Clone)]
 pub struct Iter<'a, K, V> {
-    pub iter: slice::Iter<'a, Bucket<K, V>>,
+    pub(crate) iter: slice::Iter<'a, Bucket<K, V>>,
 }
 
 impl<'a, K, V> Iterator for Iter<'a, K, V> {
@@ -322,10 +1131,30 @@ impl<'a, K, V> Iterator for Iter<'a, K, V> {
     fn next(&mut self) -> Option<Self::Item> {
         self.iter.next().map(|bucket| (&bucket.key, &bucket.value))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
 }
 
+impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|bucket| (&bucket.key, &bucket.value))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Iter<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Iter<'a, K, V> {}
+
+/// An iterator over the key-value pairs of a map with mutable references to the values, in the
+/// order of the map.
+///
+/// Created by [`FcHashMap::iter_mut()`](crate::FcHashMap::iter_mut).
 pub struct IterMut<'a, K, V> {
-    pub iter: slice::IterMut<'a, Bucket<K, V>>,
+    pub(crate) iter: slice::IterMut<'a, Bucket<K, V>>,
 }
 
 impl<'a, K, V> Iterator for IterMut<'a, K, V> {
@@ -336,4 +1165,222 @@ impl<'a, K, V> Iterator for IterMut<'a, K, V> {
             .next()
             .map(|bucket| (&bucket.key, &mut bucket.value))
     }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for IterMut<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|bucket| (&bucket.key, &mut bucket.value))
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for IterMut<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for IterMut<'a, K, V> {}
+
+/// An iterator over the key-value pairs of a map in chunks of `N` pairs, in the order of the map.
+///
+/// Created by [`FcHashMap::iter_chunks()`](crate::FcHashMap::iter_chunks). Every chunk is an
+/// [`Iter`] over `N` consecutive entries of the storage of the map, only the last one may hold
+/// fewer.
+#[derive(Clone)]
+pub struct Chunks<'a, K, V, const N: usize> {
+    iter: slice::Chunks<'a, Bucket<K, V>>,
+}
+
+impl<'a, K, V, const N: usize> Chunks<'a, K, V, N> {
+    // slice::chunks() panics on a chunk size of 0, so it is rejected at compile time instead
+    const N_IS_NOT_ZERO: () = assert!(N > 0, "the chunk size of iter_chunks() must not be 0");
+
+    pub(crate) fn new(buckets: &'a [Bucket<K, V>]) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::N_IS_NOT_ZERO;
+        Chunks {
+            iter: buckets.chunks(N),
+        }
+    }
+}
+
+impl<'a, K, V, const N: usize> Iterator for Chunks<'a, K, V, N> {
+    type Item = Iter<'a, K, V>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|chunk| Iter { iter: chunk.iter() })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V, const N: usize> DoubleEndedIterator for Chunks<'a, K, V, N> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|chunk| Iter { iter: chunk.iter() })
+    }
+}
+
+impl<'a, K, V, const N: usize> ExactSizeIterator for Chunks<'a, K, V, N> {}
+
+impl<'a, K, V, const N: usize> FusedIterator for Chunks<'a, K, V, N> {}
+
+/// An iterator over the keys of a map, in the order of the map.
+///
+/// Created by [`FcHashMap::keys()`](crate::FcHashMap::keys).
+#[derive(Clone)]
+pub struct Keys<'a, K, V> {
+    pub(crate) iter: slice::Iter<'a, Bucket<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Keys<'a, K, V> {
+    type Item = &'a K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|bucket| &bucket.key)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Keys<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|bucket| &bucket.key)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Keys<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Keys<'a, K, V> {}
+
+/// An iterator over the values of a map, in the order of the map.
+///
+/// Created by [`FcHashMap::values()`](crate::FcHashMap::values).
+#[derive(Clone)]
+pub struct Values<'a, K, V> {
+    pub(crate) iter: slice::Iter<'a, Bucket<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Values<'a, K, V> {
+    type Item = &'a V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|bucket| &bucket.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Values<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|bucket| &bucket.value)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for Values<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for Values<'a, K, V> {}
+
+/// An iterator over mutable references to the values of a map, in the order of the map.
+///
+/// Created by [`FcHashMap::values_mut()`](crate::FcHashMap::values_mut).
+pub struct ValuesMut<'a, K, V> {
+    pub(crate) iter: slice::IterMut<'a, Bucket<K, V>>,
+}
+
+impl<'a, K, V> Iterator for ValuesMut<'a, K, V> {
+    type Item = &'a mut V;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|bucket| &mut bucket.value)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for ValuesMut<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(|bucket| &mut bucket.value)
+    }
+}
+
+impl<'a, K, V> ExactSizeIterator for ValuesMut<'a, K, V> {}
+
+impl<'a, K, V> FusedIterator for ValuesMut<'a, K, V> {}
+
+/// An iterator that moves the key-value pairs out of a map, in the order of the map.
+///
+/// Created by the `into_iter()` method of [`FcHashMap`](crate::FcHashMap).
+pub struct IntoIter<K, V, const CAP: usize> {
+    pub(crate) iter: arrayvec::IntoIter<Bucket<K, V>, CAP>,
+}
+
+impl<K, V, const CAP: usize> Iterator for IntoIter<K, V, CAP> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(|bucket| (bucket.key, bucket.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<K, V, const CAP: usize> DoubleEndedIterator for IntoIter<K, V, CAP> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter
+            .next_back()
+            .map(|bucket| (bucket.key, bucket.value))
+    }
+}
+
+impl<K, V, const CAP: usize> ExactSizeIterator for IntoIter<K, V, CAP> {}
+
+impl<K, V, const CAP: usize> FusedIterator for IntoIter<K, V, CAP> {}
+
+/// An iterator that removes and yields the entries of a map for which a predicate returns true.
+///
+/// Created by [`FcHashMap::extract_if()`](crate::FcHashMap::extract_if).
+pub struct ExtractIf<'a, K, V, F, const CAP: usize> {
+    map: &'a mut Map<K, V, CAP>,
+    b_idx: usize,
+    pred: F,
+}
+
+impl<'a, K, V, F, const CAP: usize> ExtractIf<'a, K, V, F, CAP> {
+    pub(crate) fn new(map: &'a mut Map<K, V, CAP>, pred: F) -> Self {
+        let b_idx = map.buckets.len();
+        ExtractIf { map, b_idx, pred }
+    }
+}
+
+impl<'a, K, V, F, const CAP: usize> Iterator for ExtractIf<'a, K, V, F, CAP>
+where
+    F: FnMut(&K, &mut V) -> bool,
+{
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // The buckets are visited from the back, so the bucket that swap_pop() moves into a
+        // removed place has always been visited already
+        while self.b_idx > 0 {
+            self.b_idx -= 1;
+            let bucket = &mut self.map.buckets[self.b_idx];
+            if (self.pred)(&bucket.key, &mut bucket.value) {
+                let h_idx = self.map.h_idx_of(self.b_idx);
+                return Some(self.map.remove_found(h_idx, self.b_idx));
+            }
+        }
+        None
+    }
 }
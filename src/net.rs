@@ -0,0 +1,172 @@
+//! Key types and maps for network stacks, e.g. together with smoltcp.
+//!
+//! Neighbor caches and flow tables are needed by almost every networking application. Their keys
+//! are addresses and port numbers, whose entropy is unevenly spread: the addresses of a local
+//! network share their prefix, and the ports of a client are often consecutive. The key types of
+//! this module mix all of their bits (see [`mix32()`](crate::key::mix32)), so they spread well
+//! over the hash table.
+//!
+//! The key types hold plain octets, so they don't depend on a specific network stack. With
+//! smoltcp, an `Ipv4Address` converts with `Ipv4Key(addr.0)` and an `EthernetAddress` with
+//! `MacAddr(addr.0)`.
+//!
+//! Only available with the `net` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use fchashmap::net::{FlowKey, FlowTable, Ipv4Key, MacAddr, NeighborCache};
+//!
+//! let mut neighbors = NeighborCache::<Ipv4Key, 16>::new();
+//! neighbors.insert(Ipv4Key([192, 168, 1, 1]), MacAddr([2, 0, 0, 0, 0, 1]), 0, 60_000);
+//! assert!(neighbors.get(&Ipv4Key([192, 168, 1, 1]), 1000).is_some());
+//!
+//! let mut flows = FlowTable::<u32, 64>::new();
+//! let flow = FlowKey::v4([10, 0, 0, 1], 49152, [10, 0, 0, 2], 80, FlowKey::TCP);
+//! flows.insert(flow, 0).unwrap();
+//! *flows.get_mut(&flow).unwrap() += 1;
+//! assert_eq!(flows.get(&flow), Some(&1));
+//! ```
+use crate::key::{combine, mix32};
+use crate::{FcHashMap, FcLruTtlCache};
+use hash32::{Hash, Hasher};
+
+// Fold octets into a mixed 32 bit hash value, word by word
+fn hash_octets(octets: &[u8]) -> u32 {
+    octets.chunks(4).fold(0, |seed, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        combine(seed, mix32(u32::from_be_bytes(word)))
+    })
+}
+
+/// An IPv4 address as key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Ipv4Key(pub [u8; 4]);
+
+impl Hash for Ipv4Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(&mix32(u32::from_be_bytes(self.0)).to_le_bytes());
+    }
+}
+
+/// An IPv6 address as key.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Ipv6Key(pub [u8; 16]);
+
+impl Hash for Ipv6Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(&hash_octets(&self.0).to_le_bytes());
+    }
+}
+
+/// An IPv4 or IPv6 address as key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IpKey {
+    /// An IPv4 address
+    V4(Ipv4Key),
+    /// An IPv6 address
+    V6(Ipv6Key),
+}
+
+impl Hash for IpKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            IpKey::V4(key) => key.hash(state),
+            IpKey::V6(key) => key.hash(state),
+        }
+    }
+}
+
+impl From<Ipv4Key> for IpKey {
+    fn from(key: Ipv4Key) -> Self {
+        IpKey::V4(key)
+    }
+}
+
+impl From<Ipv6Key> for IpKey {
+    fn from(key: Ipv6Key) -> Self {
+        IpKey::V6(key)
+    }
+}
+
+/// An Ethernet MAC address, the value of a neighbor cache.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MacAddr(pub [u8; 6]);
+
+impl Hash for MacAddr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(&hash_octets(&self.0).to_le_bytes());
+    }
+}
+
+/// The 5-tuple of a transport layer flow: addresses and ports of both ends and the protocol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct FlowKey {
+    /// The source address.
+    pub src_addr: IpKey,
+    /// The source port.
+    pub src_port: u16,
+    /// The destination address.
+    pub dst_addr: IpKey,
+    /// The destination port.
+    pub dst_port: u16,
+    /// The IP protocol number, e.g. [`FlowKey::TCP`].
+    pub protocol: u8,
+}
+
+impl FlowKey {
+    /// The IP protocol number of TCP.
+    pub const TCP: u8 = 6;
+    /// The IP protocol number of UDP.
+    pub const UDP: u8 = 17;
+
+    /// Creates the key of an IPv4 flow.
+    pub const fn v4(
+        src_addr: [u8; 4],
+        src_port: u16,
+        dst_addr: [u8; 4],
+        dst_port: u16,
+        protocol: u8,
+    ) -> Self {
+        FlowKey {
+            src_addr: IpKey::V4(Ipv4Key(src_addr)),
+            src_port,
+            dst_addr: IpKey::V4(Ipv4Key(dst_addr)),
+            dst_port,
+            protocol,
+        }
+    }
+
+    /// Returns the key of the opposite direction of the flow.
+    pub const fn reversed(&self) -> Self {
+        FlowKey {
+            src_addr: self.dst_addr,
+            src_port: self.dst_port,
+            dst_addr: self.src_addr,
+            dst_port: self.src_port,
+            protocol: self.protocol,
+        }
+    }
+}
+
+impl Hash for FlowKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let addr = |key: &IpKey| match key {
+            IpKey::V4(key) => mix32(u32::from_be_bytes(key.0)),
+            IpKey::V6(key) => hash_octets(&key.0),
+        };
+        let ports = u32::from(self.src_port) << 16 | u32::from(self.dst_port);
+        let hash = combine(addr(&self.src_addr), addr(&self.dst_addr));
+        let hash = combine(combine(hash, mix32(ports)), u32::from(self.protocol));
+        state.write(&hash.to_le_bytes());
+    }
+}
+
+/// A neighbor cache (ARP or NDP) from addresses to MAC addresses, whose entries expire.
+///
+/// `A` is [`Ipv4Key`], [`Ipv6Key`] or [`IpKey`].
+pub type NeighborCache<A, const CAP: usize> = FcLruTtlCache<A, MacAddr, CAP>;
+
+/// A flow table from the 5-tuple of a flow to its state `V`.
+pub type FlowTable<V, const CAP: usize> = FcHashMap<FlowKey, V, CAP>;
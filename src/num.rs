@@ -0,0 +1,27 @@
+/// Saturating addition for the primitive integer types.
+///
+/// This trait is used by [`FcHashMap::sum_values()`](crate::FcHashMap::sum_values) so that
+/// summing up counters never panics on overflow, neither in debug nor in release builds.
+pub trait SaturatingAdd: Copy {
+    /// The neutral element of the addition.
+    const ZERO: Self;
+
+    /// Adds `rhs` to `self`, saturating at the numeric bounds instead of overflowing.
+    fn saturating_add(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_saturating_add {
+    ($($t:ty),*) => {
+        $(
+            impl SaturatingAdd for $t {
+                const ZERO: Self = 0;
+
+                fn saturating_add(self, rhs: Self) -> Self {
+                    <$t>::saturating_add(self, rhs)
+                }
+            }
+        )*
+    };
+}
+
+impl_saturating_add!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
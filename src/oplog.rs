@@ -0,0 +1,288 @@
+//! A compact binary log of map operations.
+//!
+//! The log is written by a [`Recorder`] on the target (with the `recorder` feature). Every
+//! operation is stored as a record of [`RECORD_LEN`] bytes: one byte with the kind of the
+//! operation and its outcome, followed by the 32 bit hash value of the key in little endian byte
+//! order. The keys and values themselves are not logged. The hash value decides where a key is
+//! placed in the map, so replaying a log with the [`replay`](crate::replay) module (on the host,
+//! with the `std` feature) rebuilds exactly the hash table the target had.
+//!
+//! ## Example
+//!
+//! ```
+//! use fchashmap::oplog::{records, OpKind, OpRecord, Outcome};
+//!
+//! let record = OpRecord {
+//!     kind: OpKind::Insert,
+//!     key_hash: 0x1234_5678,
+//!     outcome: Outcome::Miss,
+//! };
+//! let log = record.to_bytes();
+//!
+//! let mut decoded = records(&log);
+//! assert_eq!(decoded.next(), Some(Some(record)));
+//! assert_eq!(decoded.next(), None);
+//! ```
+#[cfg(feature = "recorder")]
+use crate::FcHashMap;
+#[cfg(feature = "recorder")]
+use hash32::{BuildHasher, BuildHasherDefault, FnvHasher, Hash, Hasher};
+
+/// The length of a record in bytes.
+pub const RECORD_LEN: usize = 5;
+
+/// The kind of a logged operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OpKind {
+    /// `insert()` of a key
+    Insert,
+    /// `remove()` of a key
+    Remove,
+    /// `get()` of a key
+    Get,
+}
+
+/// The outcome of a logged operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The key was not in the map. For an insert, a new entry was added.
+    Miss,
+    /// The key was in the map. For an insert, its value was replaced.
+    Hit,
+    /// The insert of a new key was rejected, as the map was full.
+    Rejected,
+}
+
+/// A logged operation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OpRecord {
+    /// The kind of the operation.
+    pub kind: OpKind,
+    /// The 32 bit hash value of the key, as calculated by the map.
+    pub key_hash: u32,
+    /// The outcome of the operation.
+    pub outcome: Outcome,
+}
+
+impl OpRecord {
+    /// Encodes the record.
+    pub fn to_bytes(&self) -> [u8; RECORD_LEN] {
+        let kind = match self.kind {
+            OpKind::Insert => 1,
+            OpKind::Remove => 2,
+            OpKind::Get => 3,
+        };
+        let outcome = match self.outcome {
+            Outcome::Miss => 0,
+            Outcome::Hit => 1,
+            Outcome::Rejected => 2,
+        };
+        let hash = self.key_hash.to_le_bytes();
+        [kind | outcome << 4, hash[0], hash[1], hash[2], hash[3]]
+    }
+
+    /// Decodes a record. Returns `None` if the bytes are not a valid record.
+    pub fn from_bytes(bytes: [u8; RECORD_LEN]) -> Option<Self> {
+        let kind = match bytes[0] & 0x0f {
+            1 => OpKind::Insert,
+            2 => OpKind::Remove,
+            3 => OpKind::Get,
+            _ => return None,
+        };
+        let outcome = match bytes[0] >> 4 {
+            0 => Outcome::Miss,
+            1 => Outcome::Hit,
+            2 if kind == OpKind::Insert => Outcome::Rejected,
+            _ => return None,
+        };
+        let key_hash = u32::from_le_bytes([bytes[1], bytes[2], bytes[3], bytes[4]]);
+        Some(OpRecord {
+            kind,
+            key_hash,
+            outcome,
+        })
+    }
+}
+
+/// Returns an iterator over the records of a log.
+///
+/// Invalid records, including an incomplete record at the end of the log, are returned as `None`.
+pub fn records(log: &[u8]) -> impl Iterator<Item = Option<OpRecord>> + '_ {
+    log.chunks(RECORD_LEN).map(|chunk| {
+        let mut bytes = [0; RECORD_LEN];
+        if chunk.len() != RECORD_LEN {
+            return None;
+        }
+        bytes.copy_from_slice(chunk);
+        OpRecord::from_bytes(bytes)
+    })
+}
+
+/// A recorder that logs map operations into a ring buffer.
+///
+/// The operations are done through the recorder, which calculates the hash value of the key and
+/// appends a record with the outcome to the buffer. The buffer is provided by the caller, e.g. a
+/// `static` array that a debugger reads over RTT. It holds `buf.len() / RECORD_LEN` records,
+/// when it is full the oldest record is overwritten. As a replay starts with an empty map, the
+/// buffer has to hold all operations since the map was created to be replayable.
+///
+/// Only available with the `recorder` feature.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::oplog::{records, Outcome, Recorder};
+/// use fchashmap::FcHashMap;
+///
+/// let mut buf = [0; 64];
+/// let mut recorder = Recorder::new(&mut buf);
+/// let mut map = FcHashMap::<u32, u32, 16>::new();
+///
+/// recorder.insert(&mut map, 7, 70).unwrap();
+/// assert_eq!(recorder.get(&map, &7), Some(&70));
+/// assert_eq!(recorder.remove(&mut map, &8), None);
+///
+/// let (first, second) = recorder.as_slices();
+/// assert!(second.is_empty());
+/// let outcomes: Vec<_> = records(first).map(|r| r.unwrap().outcome).collect();
+/// assert_eq!(outcomes, vec![Outcome::Miss, Outcome::Hit, Outcome::Miss]);
+/// ```
+#[cfg(feature = "recorder")]
+pub struct Recorder<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+    wrapped: bool,
+    overwritten: usize,
+}
+
+#[cfg(feature = "recorder")]
+impl<'a> Recorder<'a> {
+    /// Creates a recorder that logs into `buf`.
+    pub fn new(buf: &'a mut [u8]) -> Self {
+        Recorder {
+            buf,
+            pos: 0,
+            wrapped: false,
+            overwritten: 0,
+        }
+    }
+
+    /// Returns the recorded bytes in chronological order. The log continues from the first into
+    /// the second slice, which is empty until the buffer has wrapped around.
+    pub fn as_slices(&self) -> (&[u8], &[u8]) {
+        let end = self.capacity() * RECORD_LEN;
+        if self.wrapped {
+            (&self.buf[self.pos..end], &self.buf[..self.pos])
+        } else {
+            (&self.buf[..self.pos], &[])
+        }
+    }
+
+    /// Returns the number of records the buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.buf.len() / RECORD_LEN
+    }
+
+    /// Removes all records.
+    pub fn clear(&mut self) {
+        self.pos = 0;
+        self.wrapped = false;
+        self.overwritten = 0;
+    }
+
+    /// Looks up a key in the map and records the operation, see
+    /// [`FcHashMap::get()`](crate::FcHashMap::get).
+    pub fn get<'m, K, V, const CAP: usize>(
+        &mut self,
+        map: &'m FcHashMap<K, V, CAP>,
+        key: &K,
+    ) -> Option<&'m V>
+    where
+        K: Hash + Eq,
+    {
+        let value = map.get(key);
+        self.record(OpKind::Get, key, value.is_some());
+        value
+    }
+
+    /// Inserts a key-value pair into the map and records the operation, see
+    /// [`FcHashMap::insert()`](crate::FcHashMap::insert).
+    pub fn insert<K, V, const CAP: usize>(
+        &mut self,
+        map: &mut FcHashMap<K, V, CAP>,
+        key: K,
+        value: V,
+    ) -> Result<Option<V>, (K, V)>
+    where
+        K: Hash + Eq,
+    {
+        let key_hash = key_hash(&key);
+        let result = map.insert(key, value);
+        let outcome = match result {
+            Ok(None) => Outcome::Miss,
+            Ok(Some(_)) => Outcome::Hit,
+            Err(_) => Outcome::Rejected,
+        };
+        self.push(OpRecord {
+            kind: OpKind::Insert,
+            key_hash,
+            outcome,
+        });
+        result
+    }
+
+    /// Returns the number of records that were overwritten, because the buffer was full.
+    pub fn overwritten(&self) -> usize {
+        self.overwritten
+    }
+
+    /// Removes a key from the map and records the operation, see
+    /// [`FcHashMap::remove()`](crate::FcHashMap::remove).
+    pub fn remove<K, V, const CAP: usize>(
+        &mut self,
+        map: &mut FcHashMap<K, V, CAP>,
+        key: &K,
+    ) -> Option<V>
+    where
+        K: Hash + Eq,
+    {
+        let value = map.remove(key);
+        self.record(OpKind::Remove, key, value.is_some());
+        value
+    }
+
+    // Record a lookup or removal
+    fn record<K: Hash>(&mut self, kind: OpKind, key: &K, found: bool) {
+        let outcome = if found { Outcome::Hit } else { Outcome::Miss };
+        self.push(OpRecord {
+            kind,
+            key_hash: key_hash(key),
+            outcome,
+        });
+    }
+
+    // Append a record, overwriting the oldest one if the buffer is full
+    fn push(&mut self, record: OpRecord) {
+        if self.capacity() == 0 {
+            self.overwritten += 1;
+            return;
+        }
+        if self.wrapped {
+            self.overwritten += 1;
+        }
+        self.buf[self.pos..self.pos + RECORD_LEN].copy_from_slice(&record.to_bytes());
+        self.pos += RECORD_LEN;
+        if self.pos == self.capacity() * RECORD_LEN {
+            self.pos = 0;
+            self.wrapped = true;
+        }
+    }
+}
+
+// Calculate the 32 bit hash value of a key, the same way the map does
+#[cfg(feature = "recorder")]
+fn key_hash<K: Hash + ?Sized>(key: &K) -> u32 {
+    let mut h = BuildHasherDefault::<FnvHasher>::default().build_hasher();
+    key.hash(&mut h);
+    h.finish()
+}
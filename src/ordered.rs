@@ -0,0 +1,148 @@
+use crate::{FcHashMap, Iter, IterMut, Keys, Values, ValuesMut};
+use core::borrow::Borrow;
+use hash32::Hash;
+
+/// A map whose iteration order is always the order of insertion.
+///
+/// [`FcHashMap`] removes an entry by moving its last entry into the gap, which changes the order
+/// of the entries. This map shifts all entries behind a removed one instead, like `IndexMap`
+/// with `shift_remove()`. The position of an entry, see [`get_index()`](Self::get_index), is the
+/// number of entries inserted before it that are still in the map. Replacing the value of a key
+/// keeps its position.
+///
+/// Lookups and inserts cost the same as with [`FcHashMap`], but a removal takes time linear in
+/// `CAP`, as every index behind the removed entry has to be corrected.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::FcOrderedHashMap;
+///
+/// let mut map = FcOrderedHashMap::<&str, u32, 8>::new();
+/// map.insert("a", 1).unwrap();
+/// map.insert("b", 2).unwrap();
+/// map.insert("c", 3).unwrap();
+/// map.insert("d", 4).unwrap();
+///
+/// assert_eq!(map.remove("b"), Some(2));
+/// map.insert("a", 10).unwrap();
+/// assert!(map.keys().eq(["a", "c", "d"].iter()));
+/// assert_eq!(map.get_index(1), Some((&"c", &3)));
+/// ```
+pub struct FcOrderedHashMap<K, V, const CAP: usize> {
+    map: FcHashMap<K, V, CAP>,
+}
+
+impl<K, V, const CAP: usize> FcOrderedHashMap<K, V, CAP> {
+    /// Creates an empty map.
+    pub const fn new() -> Self {
+        FcOrderedHashMap {
+            map: FcHashMap::new(),
+        }
+    }
+
+    /// Returns the number of elements the map can hold.
+    pub fn capacity(&self) -> usize {
+        CAP
+    }
+
+    forward_to_map!();
+
+    /// Remove all key-value pairs in the map.
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+
+    /// Returns the key-value pair at the position `idx` in the order of insertion.
+    pub fn get_index(&self, idx: usize) -> Option<(&K, &V)> {
+        self.map.get_index(idx)
+    }
+
+    /// Returns the position of the key in the order of insertion.
+    pub fn index_of<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.index_of(key)
+    }
+
+    /// Inserts a key-value pair into the map, see [`FcHashMap::insert()`].
+    ///
+    /// A new key is placed behind all other keys, a key that is already in the map keeps its
+    /// position.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)>
+    where
+        K: Hash + Eq,
+    {
+        self.map.insert(key, value)
+    }
+
+    /// Return an iterator over the key-value pairs of the map, in the order of insertion.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.map.iter()
+    }
+
+    /// Return an iterator over the key-value pairs of the map with mutable references to the
+    /// values, in the order of insertion.
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        self.map.iter_mut()
+    }
+
+    /// Return an iterator over the keys of the map, in the order of insertion.
+    pub fn keys(&self) -> Keys<'_, K, V> {
+        self.map.keys()
+    }
+
+    /// Removes the most recently inserted entry and returns it.
+    pub fn pop(&mut self) -> Option<(K, V)> {
+        self.map.pop()
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously
+    /// in the map. All entries behind it move one position forward.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.remove_entry(key).map(|(_, v)| v)
+    }
+
+    /// Removes a key from the map, returning the stored key and value if the key was previously
+    /// in the map. All entries behind it move one position forward.
+    pub fn remove_entry<Q>(&mut self, key: &Q) -> Option<(K, V)>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let (h_idx, b_idx) = self.map.map.find(key)?;
+        Some(self.map.map.shift_remove_found(h_idx, b_idx))
+    }
+
+    /// Retains only the elements specified by the predicate, keeping the order of the remaining
+    /// elements.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&K, &mut V) -> bool,
+    {
+        self.map.map.retain_ordered(f);
+    }
+
+    /// Return an iterator over the values of the map, in the order of insertion.
+    pub fn values(&self) -> Values<'_, K, V> {
+        self.map.values()
+    }
+
+    /// Return an iterator over mutable references to the values of the map, in the order of
+    /// insertion.
+    pub fn values_mut(&mut self) -> ValuesMut<'_, K, V> {
+        self.map.values_mut()
+    }
+}
+
+impl<K, V, const CAP: usize> Default for FcOrderedHashMap<K, V, CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
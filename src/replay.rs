@@ -0,0 +1,141 @@
+//! Replay of logged map operations on the host.
+//!
+//! A log recorded on the target (see [`oplog`](crate::oplog)) is replayed against an
+//! [`FcHashMap`] and a `std::collections::HashMap`. The keys are represented by their hash values,
+//! so the replayed map has exactly the hash table the target had after the same operations.
+//! After every step, the outcome of the replayed map is compared with the outcome on the target
+//! and with the outcome of the std map:
+//!
+//! - If the replayed map differs from the target, the target did not behave deterministically,
+//!   e.g. because its memory was corrupted from outside.
+//! - If the replayed map differs from the std map, the map itself is wrong.
+//!
+//! Only available with the `std` feature.
+//!
+//! ## Example
+//!
+//! ```
+//! use fchashmap::oplog::{OpKind, OpRecord, Outcome};
+//! use fchashmap::replay::replay;
+//!
+//! let mut log = Vec::new();
+//! for (kind, outcome) in [
+//!     (OpKind::Insert, Outcome::Miss),
+//!     (OpKind::Get, Outcome::Hit),
+//!     (OpKind::Remove, Outcome::Hit),
+//! ]
+//! .iter()
+//! {
+//!     let record = OpRecord { kind: *kind, key_hash: 0xabcd, outcome: *outcome };
+//!     log.extend_from_slice(&record.to_bytes());
+//! }
+//!
+//! let map = replay::<64>(&log).unwrap();
+//! assert!(map.is_empty());
+//! ```
+use crate::oplog::{records, OpKind, OpRecord, Outcome};
+use crate::FcHashMap;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The error of a replay.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The record at this step is not a valid record.
+    Malformed { step: usize },
+    /// The outcome of a step differs between the target, the replayed map and the std map.
+    Diverged(Divergence),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Malformed { step } => write!(f, "malformed record at step {}", step),
+            ReplayError::Diverged(d) => write!(
+                f,
+                "step {}: {:?} of key hash {:#010x} was {:?} on the target, {:?} in the replay \
+                 and {:?} in the std map",
+                d.step, d.record.kind, d.record.key_hash, d.record.outcome, d.replayed, d.reference
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// A step whose outcome differs between the target, the replayed map and the std map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The index of the record in the log.
+    pub step: usize,
+    /// The record, with the outcome on the target.
+    pub record: OpRecord,
+    /// The outcome of the replayed map.
+    pub replayed: Outcome,
+    /// The outcome of the std map.
+    pub reference: Outcome,
+}
+
+/// Replays a log on a map with the capacity `CAP` of the map on the target.
+///
+/// Returns the replayed map, whose keys are the hash values of the original keys, or the first
+/// step at which the outcomes diverge. Different keys with the same 32 bit hash value are
+/// indistinguishable in the log.
+pub fn replay<const CAP: usize>(log: &[u8]) -> Result<FcHashMap<u32, (), CAP>, ReplayError> {
+    let mut map = FcHashMap::<u32, (), CAP>::new();
+    let mut reference = HashMap::new();
+    for (step, record) in records(log).enumerate() {
+        let record = record.ok_or(ReplayError::Malformed { step })?;
+        let key = record.key_hash;
+        let (replayed, expected) = match record.kind {
+            OpKind::Insert => {
+                let replayed = match map.map.insert_with_hash(key, (), key) {
+                    Ok(None) => Outcome::Miss,
+                    Ok(Some(())) => Outcome::Hit,
+                    Err(_) => Outcome::Rejected,
+                };
+                // FcHashMap::insert() rejects every key on a full map, even a key in the map
+                let expected = if reference.len() == CAP {
+                    Outcome::Rejected
+                } else if reference.insert(key, ()).is_some() {
+                    Outcome::Hit
+                } else {
+                    Outcome::Miss
+                };
+                (replayed, expected)
+            }
+            OpKind::Remove => {
+                let replayed = match map.map.find_with_hash(&key, key) {
+                    Some((h_idx, b_idx)) => {
+                        map.map.remove_found(h_idx, b_idx);
+                        Outcome::Hit
+                    }
+                    None => Outcome::Miss,
+                };
+                (replayed, hit(reference.remove(&key).is_some()))
+            }
+            OpKind::Get => (
+                hit(map.map.find_with_hash(&key, key).is_some()),
+                hit(reference.contains_key(&key)),
+            ),
+        };
+        if replayed != record.outcome || replayed != expected {
+            return Err(ReplayError::Diverged(Divergence {
+                step,
+                record,
+                replayed,
+                reference: expected,
+            }));
+        }
+    }
+    Ok(map)
+}
+
+// Outcome of a lookup
+fn hit(found: bool) -> Outcome {
+    if found {
+        Outcome::Hit
+    } else {
+        Outcome::Miss
+    }
+}
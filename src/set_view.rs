@@ -0,0 +1,163 @@
+use crate::{FcHashMap, Keys};
+use core::borrow::Borrow;
+use core::fmt;
+use hash32::Hash;
+
+/// The keys of a map, seen as a set.
+///
+/// Created by [`FcHashMap::as_set()`](crate::FcHashMap::as_set). The view borrows the map, so
+/// set operations on the keys of a map need no copy of them. The other set of an operation may
+/// be the keys of a map with other values or another capacity.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::FcHashMap;
+///
+/// let mut configured = FcHashMap::<u8, &str, 8>::new();
+/// configured.insert(1, "uart").unwrap();
+/// configured.insert(2, "spi").unwrap();
+/// let mut running = FcHashMap::<u8, u32, 4>::new();
+/// running.insert(2, 115200).unwrap();
+///
+/// let configured = configured.as_set();
+/// assert!(running.as_set().is_subset(&configured));
+///
+/// let stopped: Vec<_> = configured.difference(&running.as_set()).collect();
+/// assert_eq!(stopped, vec![&1]);
+///
+/// running.insert(1, 9600).unwrap();
+/// assert_eq!(running.as_set(), configured);
+/// ```
+pub struct FcHashSetView<'a, K, V, const CAP: usize> {
+    map: &'a FcHashMap<K, V, CAP>,
+}
+
+impl<'a, K, V, const CAP: usize> FcHashSetView<'a, K, V, CAP> {
+    pub(crate) fn new(map: &'a FcHashMap<K, V, CAP>) -> Self {
+        FcHashSetView { map }
+    }
+
+    /// Returns true if the set contains the key.
+    pub fn contains<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.map.contains_key(key)
+    }
+
+    /// Return an iterator over the keys of this set that are not in `other`.
+    pub fn difference<'b, V2, const CAP2: usize>(
+        &self,
+        other: &FcHashSetView<'b, K, V2, CAP2>,
+    ) -> impl Iterator<Item = &'a K> + 'b
+    where
+        'a: 'b,
+        K: Hash + Eq,
+    {
+        let other = *other;
+        self.iter().filter(move |key| !other.contains(*key))
+    }
+
+    /// Return an iterator over the keys of this set that are also in `other`.
+    pub fn intersection<'b, V2, const CAP2: usize>(
+        &self,
+        other: &FcHashSetView<'b, K, V2, CAP2>,
+    ) -> impl Iterator<Item = &'a K> + 'b
+    where
+        'a: 'b,
+        K: Hash + Eq,
+    {
+        let other = *other;
+        self.iter().filter(move |key| other.contains(*key))
+    }
+
+    /// Returns true if the sets have no key in common.
+    pub fn is_disjoint<V2, const CAP2: usize>(
+        &self,
+        other: &FcHashSetView<'_, K, V2, CAP2>,
+    ) -> bool
+    where
+        K: Hash + Eq,
+    {
+        self.iter().all(|key| !other.contains(key))
+    }
+
+    /// Returns true if the set contains no keys.
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Returns true if all keys of this set are in `other`.
+    pub fn is_subset<V2, const CAP2: usize>(
+        &self,
+        other: &FcHashSetView<'_, K, V2, CAP2>,
+    ) -> bool
+    where
+        K: Hash + Eq,
+    {
+        self.len() <= other.len() && self.iter().all(|key| other.contains(key))
+    }
+
+    /// Returns true if all keys of `other` are in this set.
+    pub fn is_superset<V2, const CAP2: usize>(
+        &self,
+        other: &FcHashSetView<'_, K, V2, CAP2>,
+    ) -> bool
+    where
+        K: Hash + Eq,
+    {
+        other.is_subset(self)
+    }
+
+    /// Return an iterator over the keys of the set, in the order of the map.
+    pub fn iter(&self) -> Keys<'a, K, V> {
+        self.map.keys()
+    }
+
+    /// Return the number of keys in the set.
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+}
+
+// The view only holds a reference, so it is Copy whatever the keys and values are
+impl<'a, K, V, const CAP: usize> Clone for FcHashSetView<'a, K, V, CAP> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, K, V, const CAP: usize> Copy for FcHashSetView<'a, K, V, CAP> {}
+
+impl<'a, K, V, const CAP: usize> fmt::Debug for FcHashSetView<'a, K, V, CAP>
+where
+    K: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+// Sets are equal if they contain the same keys, whatever the values and capacities of their maps
+impl<'a, 'b, K, V, V2, const CAP: usize, const CAP2: usize>
+    PartialEq<FcHashSetView<'b, K, V2, CAP2>> for FcHashSetView<'a, K, V, CAP>
+where
+    K: Hash + Eq,
+{
+    fn eq(&self, other: &FcHashSetView<'b, K, V2, CAP2>) -> bool {
+        self.len() == other.len() && self.is_subset(other)
+    }
+}
+
+impl<'a, K: Hash + Eq, V, const CAP: usize> Eq for FcHashSetView<'a, K, V, CAP> {}
+
+impl<'a, K, V, const CAP: usize> IntoIterator for FcHashSetView<'a, K, V, CAP> {
+    type Item = &'a K;
+    type IntoIter = Keys<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -0,0 +1,213 @@
+use crate::FcHashMap;
+use core::borrow::Borrow;
+use core::mem;
+use hash32::Hash;
+
+// The end of the free list
+const NO_SLOT: u16 = u16::MAX;
+
+// A place for a value, or a link in the list of free places
+enum Slot<V> {
+    Used(V),
+    Free(u16),
+}
+
+impl<V> Slot<V> {
+    fn value(&self) -> &V {
+        match self {
+            Slot::Used(value) => value,
+            Slot::Free(_) => unreachable!("the index points to a free slot"),
+        }
+    }
+
+    fn value_mut(&mut self) -> &mut V {
+        match self {
+            Slot::Used(value) => value,
+            Slot::Free(_) => unreachable!("the index points to a free slot"),
+        }
+    }
+}
+
+/// A map whose values never move in memory while they are in the map.
+///
+/// [`FcHashMap`] keeps its entries dense and moves the last entry into the place of a removed
+/// one. This map stores the values in an array of slots instead, and only keeps the index of the
+/// slot in the hash table. A value stays in its slot until its key is removed, and the slot is
+/// then put on a free list to be reused by a later insert. Replacing the value of a key writes
+/// the new value into the same slot.
+///
+/// So the address of a value is stable across inserts and removals of other keys, e.g. for DMA
+/// descriptors that point into the values. This holds as long as the map itself is not moved,
+/// e.g. if it is a `static` or pinned.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::FcStableHashMap;
+///
+/// let mut map = FcStableHashMap::<u32, [u8; 16], 8>::new();
+/// map.insert(1, [0; 16]).unwrap();
+/// map.insert(2, [0; 16]).unwrap();
+/// let addr = map.get(&2).unwrap().as_ptr();
+///
+/// map.remove(&1);
+/// map.insert(3, [3; 16]).unwrap();
+/// assert_eq!(map.get(&2).unwrap().as_ptr(), addr);
+/// ```
+pub struct FcStableHashMap<K, V, const CAP: usize> {
+    index: FcHashMap<K, u16, CAP>,
+    slots: [Slot<V>; CAP],
+    // The head of the list of free slots
+    free: u16,
+    // The slots from here on have never been used
+    unused: u16,
+}
+
+impl<K, V, const CAP: usize> FcStableHashMap<K, V, CAP> {
+    // An empty slot at the end of the free list
+    const FREE: Slot<V> = Slot::Free(NO_SLOT);
+
+    /// Creates an empty map.
+    pub const fn new() -> Self {
+        FcStableHashMap {
+            index: FcHashMap::new(),
+            slots: [Self::FREE; CAP],
+            free: NO_SLOT,
+            unused: 0,
+        }
+    }
+
+    /// Returns the number of elements the map can hold.
+    pub fn capacity(&self) -> usize {
+        CAP
+    }
+
+    /// Remove all key-value pairs in the map.
+    pub fn clear(&mut self) {
+        self.index.clear();
+        for slot in self.slots[..usize::from(self.unused)].iter_mut() {
+            *slot = Self::FREE;
+        }
+        self.free = NO_SLOT;
+        self.unused = 0;
+    }
+
+    /// Returns true if the map contains a value for the specified key.
+    pub fn contains_key<Q>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.index.contains_key(key)
+    }
+
+    /// Returns the number of slots that held a value and are free now, waiting to be reused.
+    ///
+    /// Slots that have never been used don't count. Together with
+    /// [`highest_used_slot()`](Self::highest_used_slot) this shows how fragmented the slots are.
+    pub fn free_slots(&self) -> usize {
+        usize::from(self.unused) - self.index.len()
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let slot = *self.index.get(key)?;
+        Some(self.slots[usize::from(slot)].value())
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let slot = *self.index.get(key)?;
+        Some(self.slots[usize::from(slot)].value_mut())
+    }
+
+    /// Returns the index of the highest slot that holds a value, or `None` if the map is empty.
+    ///
+    /// The slots up to this index are in use or on the free list. This takes a scan over the
+    /// slots above the highest used one.
+    pub fn highest_used_slot(&self) -> Option<usize> {
+        self.slots[..usize::from(self.unused)]
+            .iter()
+            .rposition(|slot| matches!(slot, Slot::Used(_)))
+    }
+
+    /// Inserts a key-value pair into the map, see [`FcHashMap::insert()`].
+    ///
+    /// A new value takes a free slot, the value of a key that is already in the map is replaced
+    /// in its slot.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, (K, V)>
+    where
+        K: Hash + Eq,
+    {
+        if let Some(&slot) = self.index.get(&key) {
+            let old = self.slots[usize::from(slot)].value_mut();
+            return Ok(Some(mem::replace(old, value)));
+        }
+        if self.index.len() == CAP {
+            return Err((key, value));
+        }
+
+        let slot = if self.free != NO_SLOT {
+            let slot = self.free;
+            if let Slot::Free(next) = self.slots[usize::from(slot)] {
+                self.free = next;
+            }
+            slot
+        } else {
+            self.unused += 1;
+            self.unused - 1
+        };
+        self.slots[usize::from(slot)] = Slot::Used(value);
+        // The index has room, as it holds one key per used slot
+        let inserted = self.index.insert(key, slot).is_ok();
+        debug_assert!(inserted);
+        Ok(None)
+    }
+
+    /// Returns true if the map contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Return an iterator over the key-value pairs of the map.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.index
+            .iter()
+            .map(move |(key, &slot)| (key, self.slots[usize::from(slot)].value()))
+    }
+
+    /// Return the number of key-value pairs in the map.
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Removes a key from the map, returning the value at the key if the key was previously
+    /// in the map. The slot of the value becomes free.
+    pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        let slot = self.index.remove(key)?;
+        let freed = mem::replace(&mut self.slots[usize::from(slot)], Slot::Free(self.free));
+        self.free = slot;
+        match freed {
+            Slot::Used(value) => Some(value),
+            Slot::Free(_) => unreachable!("the index points to a free slot"),
+        }
+    }
+}
+
+impl<K, V, const CAP: usize> Default for FcStableHashMap<K, V, CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,60 @@
+use crate::FcHashMap;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// A map for a `static`, which hands out a `&'static mut` reference to it exactly once.
+///
+/// Large maps often are placed in a `static` to keep them off the stack, which requires a
+/// `static mut` and an unsafe block at every access. This wrapper instead works like the `take()`
+/// of the peripherals of an embedded HAL: the first call of [`take()`](StaticFcHashMap::take)
+/// returns the map, every further call returns `None`. The map is typically taken during startup
+/// and then moved into the task that owns it.
+///
+/// Only available on targets with atomic compare and swap operations.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::{FcHashMap, StaticFcHashMap};
+///
+/// static ROUTES: StaticFcHashMap<u16, u8, 1024> = StaticFcHashMap::new();
+///
+/// let routes: &'static mut FcHashMap<u16, u8, 1024> = ROUTES.take().unwrap();
+/// routes.insert(0x1234, 3).unwrap();
+///
+/// assert!(ROUTES.take().is_none());
+/// ```
+pub struct StaticFcHashMap<K, V, const CAP: usize> {
+    map: UnsafeCell<FcHashMap<K, V, CAP>>,
+    taken: AtomicBool,
+}
+
+// The map is accessed only through the one reference handed out by take()
+unsafe impl<K: Send, V: Send, const CAP: usize> Sync for StaticFcHashMap<K, V, CAP> {}
+
+impl<K, V, const CAP: usize> StaticFcHashMap<K, V, CAP> {
+    /// Creates an empty map, which has not been taken yet.
+    pub const fn new() -> Self {
+        StaticFcHashMap {
+            map: UnsafeCell::new(FcHashMap::new()),
+            taken: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the map on the first call, and `None` on all further calls.
+    #[allow(clippy::mut_from_ref)]
+    pub fn take(&'static self) -> Option<&'static mut FcHashMap<K, V, CAP>> {
+        if self.taken.swap(true, Ordering::AcqRel) {
+            None
+        } else {
+            // unsafe is ok, this is the only reference to the map that is ever created
+            Some(unsafe { &mut *self.map.get() })
+        }
+    }
+}
+
+impl<K, V, const CAP: usize> Default for StaticFcHashMap<K, V, CAP> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,14 @@
+/// A rejected insertion into a full map, together with a suggested entry to evict.
+///
+/// Returned by [`FcHashMap::insert_or_suggest()`](crate::FcHashMap::insert_or_suggest). The map
+/// is left untouched, so the caller decides whether to remove `victim` and insert the pair again,
+/// or to drop the pair.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion<K, V> {
+    /// The key that could not be inserted.
+    pub key: K,
+    /// The value that could not be inserted.
+    pub value: V,
+    /// The key of the entry suggested for eviction.
+    pub victim: K,
+}
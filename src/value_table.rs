@@ -0,0 +1,230 @@
+use arrayvec::ArrayVec;
+use core::borrow::Borrow;
+use hash32::{BuildHasher, BuildHasherDefault, FnvHasher, Hash, Hasher};
+
+// Marks an empty slot of the key index
+const SLOT_IS_EMPTY: u32 = u32::MAX;
+
+// Calculate the 15 bit hash value of a key, the same way the map does
+fn hash_of<Q: ?Sized + Hash>(key: &Q) -> u32 {
+    let mut h = BuildHasherDefault::<FnvHasher>::default().build_hasher();
+    key.hash(&mut h);
+    h.finish() & 0x7fff
+}
+
+/// An immutable hash index over a fixed set of keys.
+///
+/// The index consists of a slice of keys and a table of `CAP` slots. Both can be placed in flash
+/// memory: build the index on the host (e.g. in a build script) with
+/// [`build()`](FcKeyIndex::build), write the keys and [`slots()`](FcKeyIndex::slots) to a
+/// generated source file and create a `static` index there with
+/// [`from_raw_parts()`](FcKeyIndex::from_raw_parts). Such an index serves an
+/// [`FcValueTable`], which holds only the values in RAM.
+///
+/// As with [`FcHashMap`](crate::FcHashMap), `CAP` must be a power of 2.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::FcKeyIndex;
+///
+/// static KEYS: [u16; 3] = [0x101, 0x202, 0x303];
+///
+/// // Typically done in a build script, which prints the slots into a source file
+/// let index = FcKeyIndex::<u16, 8>::build(&KEYS).unwrap();
+/// let slots = *index.slots();
+///
+/// let index = FcKeyIndex::<u16, 8>::from_raw_parts(&KEYS, slots);
+/// assert_eq!(index.position(&0x202), Some(1));
+/// assert_eq!(index.position(&0x404), None);
+/// ```
+pub struct FcKeyIndex<K: 'static, const CAP: usize> {
+    keys: &'static [K],
+    slots: [u32; CAP],
+}
+
+impl<K: 'static, const CAP: usize> FcKeyIndex<K, CAP> {
+    // The slots are indexed by masking the hash values, so CAP is checked at compile time
+    const CAP_IS_POWER_OF_TWO: () =
+        assert!(CAP.is_power_of_two(), "the capacity of FcKeyIndex must be a power of 2");
+
+    // A slot keeps the position of its key in the low 16 bits, so it addresses at most 65536 keys
+    const CAP_FITS_SLOT: () =
+        assert!(CAP <= 0x10000, "the capacity of FcKeyIndex is limited to 65536");
+
+    /// Creates an index from the keys and the slots calculated by [`build()`](FcKeyIndex::build).
+    ///
+    /// This is a `const fn`, so the index can be a `static` in flash memory.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if there are more keys than `CAP` or if a slot points behind the keys, i.e. if the
+    /// slots were not built for these keys. For a `static` index, this is a compile error.
+    pub const fn from_raw_parts(keys: &'static [K], slots: [u32; CAP]) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CAP_IS_POWER_OF_TWO;
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CAP_FITS_SLOT;
+        assert!(keys.len() <= CAP, "FcKeyIndex has more keys than its capacity");
+        let mut s_idx = 0;
+        while s_idx < CAP {
+            let slot = slots[s_idx];
+            assert!(
+                slot == SLOT_IS_EMPTY || ((slot & 0xffff) as usize) < keys.len(),
+                "FcKeyIndex slot points behind the keys"
+            );
+            s_idx += 1;
+        }
+        FcKeyIndex { keys, slots }
+    }
+
+    /// Builds the index over `keys`.
+    ///
+    /// Returns `None` if there are more keys than `CAP` or if a key occurs twice.
+    pub fn build(keys: &'static [K]) -> Option<Self>
+    where
+        K: Hash + Eq,
+    {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CAP_IS_POWER_OF_TWO;
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CAP_FITS_SLOT;
+        if keys.len() > CAP {
+            return None;
+        }
+        let mut index = FcKeyIndex {
+            keys,
+            slots: [SLOT_IS_EMPTY; CAP],
+        };
+        for (k_idx, key) in keys.iter().enumerate() {
+            if index.position(key).is_some() {
+                return None;
+            }
+            let hash = hash_of(key);
+            let mut s_idx = hash as usize & (CAP - 1);
+            while index.slots[s_idx] != SLOT_IS_EMPTY {
+                s_idx = (s_idx + 1) & (CAP - 1);
+            }
+            index.slots[s_idx] = hash << 16 | k_idx as u32;
+        }
+        Some(index)
+    }
+
+    /// Returns the keys of the index.
+    pub fn keys(&self) -> &'static [K] {
+        self.keys
+    }
+
+    /// Returns the slots of the index, to be passed to
+    /// [`from_raw_parts()`](FcKeyIndex::from_raw_parts).
+    pub fn slots(&self) -> &[u32; CAP] {
+        &self.slots
+    }
+
+    /// Returns the position of the key in the slice of keys.
+    ///
+    /// The key may be any borrowed form of the index's key type, but `Hash` and `Eq` on the
+    /// borrowed form must match those for the key type.
+    pub fn position<Q>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        if self.keys.is_empty() {
+            return None;
+        }
+        let hash = hash_of(key);
+        let mut s_idx = hash as usize & (CAP - 1);
+        for _ in 0..CAP {
+            let slot = self.slots[s_idx];
+            if slot == SLOT_IS_EMPTY {
+                return None;
+            }
+            let k_idx = (slot & 0xffff) as usize;
+            if slot >> 16 == hash && self.keys[k_idx].borrow() == key {
+                return Some(k_idx);
+            }
+            s_idx = (s_idx + 1) & (CAP - 1);
+        }
+        None
+    }
+}
+
+/// A table with a fixed set of keys, whose values are stored in RAM.
+///
+/// The keys and the hash index live in an [`FcKeyIndex`], which can be placed in flash memory.
+/// Only the values occupy RAM. Keys can't be added or removed, but every value can be changed.
+///
+/// ## Example
+///
+/// ```
+/// use fchashmap::{FcKeyIndex, FcValueTable};
+///
+/// static KEYS: [u16; 3] = [0x101, 0x202, 0x303];
+/// // Slots as calculated by FcKeyIndex::build() for these keys
+/// static SLOTS: [u32; 4] = [0x10a3_0002, 0x0fc5_0001, 0xffff_ffff, 0x1ef7_0000];
+/// static INDEX: FcKeyIndex<u16, 4> = FcKeyIndex::from_raw_parts(&KEYS, SLOTS);
+///
+/// let mut calibration = FcValueTable::new(&INDEX, |_| 1.0_f32);
+/// *calibration.get_mut(&0x202).unwrap() = 1.05;
+///
+/// assert_eq!(calibration.get(&0x202), Some(&1.05));
+/// assert_eq!(calibration.get(&0x303), Some(&1.0));
+/// assert_eq!(calibration.get(&0x404), None);
+/// ```
+pub struct FcValueTable<K: 'static, V, const CAP: usize> {
+    index: &'static FcKeyIndex<K, CAP>,
+    values: ArrayVec<V, CAP>,
+}
+
+impl<K: 'static, V, const CAP: usize> FcValueTable<K, V, CAP> {
+    /// Creates a table over the keys of `index`, with the initial values calculated by `init`.
+    pub fn new<F>(index: &'static FcKeyIndex<K, CAP>, init: F) -> Self
+    where
+        F: FnMut(&K) -> V,
+    {
+        FcValueTable {
+            index,
+            values: index.keys.iter().map(init).collect(),
+        }
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    pub fn get<Q>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.index.position(key).map(|idx| &self.values[idx])
+    }
+
+    /// Returns a mutable reference to the value corresponding to the key.
+    pub fn get_mut<Q>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+        Q: ?Sized + Hash + Eq,
+    {
+        self.index.position(key).map(move |idx| &mut self.values[idx])
+    }
+
+    /// Returns true if the table contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Return an iterator over the key-value pairs of the table, in the order of the keys.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.index.keys.iter().zip(self.values.iter())
+    }
+
+    /// Return an iterator over the keys and mutable references to the values of the table, in
+    /// the order of the keys.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&K, &mut V)> {
+        self.index.keys.iter().zip(self.values.iter_mut())
+    }
+
+    /// Return the number of key-value pairs in the table.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+}
@@ -0,0 +1,54 @@
+mod common;
+
+use common::SEED;
+use fchashmap::{BulkLoader, FcHashMap};
+use rand_core::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use std::collections::HashMap;
+
+// Load `count` random keys with the bulk loader and with insert() and compare the maps
+fn compare(count: usize) {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut std_map = HashMap::new();
+    while std_map.len() < count {
+        std_map.insert(rng.next_u32(), rng.next_u32());
+    }
+
+    let mut loader = BulkLoader::<u32, u32, 1024>::new();
+    for (k, v) in std_map.iter() {
+        loader.push(*k, *v).unwrap();
+    }
+    let mut map = loader.finish();
+    assert_eq!(map.len(), count);
+
+    for (k, v) in std_map.iter() {
+        assert_eq!(map.get(k), Some(v));
+    }
+    for _ in 0..count {
+        assert_eq!(map.get(&rng.next_u32()), None);
+    }
+
+    // The map must stay fully functional after bulk loading
+    for (k, v) in std_map.iter() {
+        assert_eq!(map.remove(k), Some(*v));
+    }
+    assert!(map.is_empty());
+}
+
+#[test]
+fn bulk_loader() {
+    for count in [0, 1, 100, 512, 900, 1023, 1024] {
+        compare(count);
+    }
+}
+
+#[test]
+fn bulk_loader_overflow() {
+    let mut loader = BulkLoader::<u32, u32, 4>::new();
+    for key in 0..4 {
+        loader.push(key, key).unwrap();
+    }
+    assert_eq!(loader.push(4, 4), Err((4, 4)));
+    let map: FcHashMap<u32, u32, 4> = loader.finish();
+    assert_eq!(map.len(), 4);
+}
@@ -0,0 +1,60 @@
+use fchashmap::FcLruTtlCache;
+
+// An expired entry is evicted before the least recently used one
+#[test]
+fn expired_before_lru() {
+    let mut cache = FcLruTtlCache::<u32, u32, 4>::new();
+    cache.insert(1, 10, 0, 1000);
+    cache.insert(2, 20, 0, 50);
+    cache.insert(3, 30, 0, 1000);
+    cache.insert(4, 40, 0, 1000);
+    assert!(cache.get(&1, 10).is_some());
+
+    // Entry 2 has expired, although entry 3 was used less recently
+    assert_eq!(cache.insert(5, 50, 100, 1000), Some((2, 20)));
+    // Now no entry has expired and entry 3 is the least recently used
+    assert_eq!(cache.insert(6, 60, 100, 1000), Some((3, 30)));
+    assert_eq!(cache.len(), 4);
+}
+
+// Replacing a value renews the time to live and gives back the old value
+#[test]
+fn replace() {
+    let mut cache = FcLruTtlCache::<&str, u32, 4>::new();
+    assert_eq!(cache.insert("a", 1, 0, 100), None);
+    assert_eq!(cache.insert("a", 2, 50, 100), Some(("a", 1)));
+    assert_eq!(cache.peek(&"a", 120), Some(&2));
+    assert_eq!(cache.get(&"a", 150), None);
+    assert!(cache.is_empty());
+}
+
+// The tick counter may wrap around
+#[test]
+fn wrapping_time() {
+    let mut cache = FcLruTtlCache::<u32, u32, 8>::new();
+    let start = u32::MAX - 10;
+    cache.insert(1, 10, start, 100);
+    cache.insert(2, 20, start, 5);
+    assert!(cache.contains_key(&1, start.wrapping_add(50)));
+    assert!(!cache.contains_key(&2, start.wrapping_add(50)));
+    assert_eq!(cache.purge_expired(start.wrapping_add(50)), 1);
+    assert_eq!(cache.get(&1, start.wrapping_add(99)), Some(&10));
+    assert_eq!(cache.get(&1, start.wrapping_add(100)), None);
+}
+
+// A full cache evicts the least recently used entries in the order of their use
+#[test]
+fn lru_order() {
+    let mut cache = FcLruTtlCache::<u32, u32, 8>::new();
+    for key in 0..8 {
+        cache.insert(key, key, 0, 1000);
+    }
+    for key in [3, 1, 7].iter() {
+        cache.get(key, 1);
+    }
+    let mut evicted = Vec::new();
+    for key in 8..16 {
+        evicted.push(cache.insert(key, key, 2, 1000).unwrap().0);
+    }
+    assert_eq!(evicted, vec![0, 2, 4, 5, 6, 3, 1, 7]);
+}
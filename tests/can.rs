@@ -0,0 +1,53 @@
+#![cfg(feature = "can")]
+use fchashmap::can::{ExtendedId, J1939Key, StandardId, StandardIdMap};
+use std::collections::BTreeMap;
+
+#[test]
+fn id_ranges() {
+    assert_eq!(StandardId::new(0x7ff), Some(StandardId::MAX));
+    assert_eq!(StandardId::new(0x800), None);
+    assert_eq!(ExtendedId::new(0x1fff_ffff), Some(ExtendedId::MAX));
+    assert_eq!(ExtendedId::new(0x2000_0000), None);
+    assert_eq!(J1939Key::new(0x4_0000, 0), None);
+}
+
+#[test]
+fn j1939_pgn() {
+    // PDU2: the PDU specific field is part of the PGN
+    let key = J1939Key::from_id(ExtendedId::new(0x18fe_f100).unwrap());
+    assert_eq!((key.pgn(), key.source_address()), (0xfef1, 0x00));
+
+    // PDU1: the PDU specific field is the destination address, requests to different
+    // destinations share the PGN
+    let to_3 = J1939Key::from_id(ExtendedId::new(0x18ea_03f9).unwrap());
+    let to_4 = J1939Key::from_id(ExtendedId::new(0x18ea_04f9).unwrap());
+    assert_eq!((to_3.pgn(), to_3.source_address()), (0xea00, 0xf9));
+    assert_eq!(to_3, to_4);
+
+    // The priority is not part of the key
+    let high = J1939Key::from_id(ExtendedId::new(0x0cf0_0400).unwrap());
+    let low = J1939Key::from_id(ExtendedId::new(0x1cf0_0400).unwrap());
+    assert_eq!(high, low);
+}
+
+// StandardIdMap must behave like a std map
+#[test]
+fn standard_id_map() {
+    let mut map = Box::new(StandardIdMap::<u32>::new());
+    let mut std_map = BTreeMap::new();
+    for i in 0..3000_u32 {
+        let id = StandardId::new((i * 7 % 2048) as u16).unwrap();
+        if i % 3 == 0 {
+            assert_eq!(map.remove(id), std_map.remove(&id.as_raw()));
+        } else {
+            assert_eq!(map.insert(id, i), std_map.insert(id.as_raw(), i));
+        }
+        assert_eq!(map.len(), std_map.len());
+    }
+    let entries: Vec<_> = map.iter().map(|(id, v)| (id.as_raw(), *v)).collect();
+    assert_eq!(entries, std_map.into_iter().collect::<Vec<_>>());
+
+    map.clear();
+    assert!(map.is_empty());
+    assert_eq!(map.iter().count(), 0);
+}
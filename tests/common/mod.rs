@@ -0,0 +1,23 @@
+// Helpers shared by the integration tests
+#![allow(dead_code)]
+
+use fchashmap::FcHashMap;
+use rand_core::RngCore;
+use rand_xorshift::XorShiftRng;
+
+pub const SEED: u64 = 1234567890987654321;
+
+// Insert random keys below key_range until the map holds len entries. The value of a key is
+// calculated from it, so an existing key keeps its value.
+pub fn fill<const CAP: usize>(
+    rng: &mut XorShiftRng,
+    map: &mut FcHashMap<u32, u32, CAP>,
+    len: usize,
+    key_range: u32,
+    value: impl Fn(u32) -> u32,
+) {
+    while map.len() < len {
+        let key = rng.next_u32() % key_range;
+        map.insert(key, value(key)).unwrap();
+    }
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use common::{fill, SEED};
+use fchashmap::FcHashMap;
+use rand_core::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+const MAP_SIZE: usize = 4096;
+
+// The documented bounds of the mean number of places read by a lookup: load in percent, bound for
+// keys in the map, bound for missing keys. They are about 20% above the values expected for
+// linear probing with random keys, which the map reaches.
+const BOUNDS: [(usize, f32, f32); 3] = [(50, 1.8, 3.0), (75, 3.0, 10.0), (90, 6.5, 60.0)];
+
+// The mean number of probes must stay within the documented bounds for random keys
+#[test]
+fn mean_probes_within_bounds() {
+    for &(load, hit, miss) in BOUNDS.iter() {
+        let stats = filled(load).probe_stats();
+        assert!(stats.mean_hit_probes() < hit, "{}%: {:?}", load, stats);
+        assert!(stats.mean_miss_probes() < miss, "{}%: {:?}", load, stats);
+    }
+}
+
+// Removals must not degrade the layout: after many removals and inserts at a constant load, the
+// bounds must still hold
+#[test]
+fn mean_probes_after_churn() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    for &(load, hit, miss) in BOUNDS.iter() {
+        let mut map = filled(load);
+        let mut keys: Vec<u32> = map.keys().copied().collect();
+        for _ in 0..4 * MAP_SIZE {
+            let idx = rng.next_u32() as usize % keys.len();
+            map.remove(&keys[idx]).unwrap();
+            loop {
+                let key = rng.next_u32();
+                if map.insert(key, 0).unwrap().is_none() {
+                    keys[idx] = key;
+                    break;
+                }
+            }
+        }
+        let stats = map.probe_stats();
+        assert!(stats.mean_hit_probes() < hit, "{}%: {:?}", load, stats);
+        assert!(stats.mean_miss_probes() < miss, "{}%: {:?}", load, stats);
+    }
+}
+
+// Print the measured numbers with `cargo test --test complexity -- --nocapture`
+#[test]
+fn report() {
+    println!("load  mean hit  max hit  mean miss  max miss");
+    for load in [25, 50, 75, 80, 90, 95].iter() {
+        let stats = filled(*load).probe_stats();
+        println!(
+            "{:3}%  {:8.2}  {:7}  {:9.2}  {:8}",
+            load,
+            stats.mean_hit_probes(),
+            stats.max_hit_probes,
+            stats.mean_miss_probes(),
+            stats.max_miss_probes
+        );
+    }
+}
+
+fn filled(load: usize) -> Box<FcHashMap<u32, u32, MAP_SIZE>> {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = Box::new(FcHashMap::new());
+    fill(&mut rng, &mut map, MAP_SIZE * load / 100, u32::MAX, |_| 0);
+    map
+}
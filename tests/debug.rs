@@ -0,0 +1,44 @@
+use fchashmap::FcHashMap;
+use std::collections::BTreeMap;
+
+#[test]
+fn debug_concise() {
+    let mut map = FcHashMap::<u32, u32, 8>::new();
+    map.insert(1, 10).unwrap();
+    assert_eq!(format!("{:?}", map), "{1: 10}");
+}
+
+#[test]
+fn debug_alternate() {
+    let mut map = FcHashMap::<u32, u32, 8>::new();
+    map.insert(1, 10).unwrap();
+    map.insert(2, 20).unwrap();
+    let s = format!("{:#?}", map);
+    assert!(s.starts_with("FcHashMap {"));
+    assert!(s.contains("len: 2,"));
+    assert!(s.contains("capacity: 8,"));
+    assert!(s.contains("load_percent: 25,"));
+    assert!(s.contains("max_displacement: "));
+    assert!(s.contains("1: 10,"));
+    assert!(s.contains("2: 20,"));
+}
+
+#[test]
+fn debug_truncated() {
+    let map: FcHashMap<u32, u32, 64> = (0..40).map(|i| (i, i)).collect();
+    assert_eq!(format!("{:?}", map.debug_truncated(0)), "{..: 40 more}");
+    let all: BTreeMap<_, _> = map.iter().collect();
+    assert_eq!(format!("{:?}", map.debug_truncated(40)), format!("{:?}", all));
+
+    let pretty = format!("{:#?}", map.debug_truncated(2));
+    assert_eq!(pretty, "{\n    0: 0,\n    1: 1,\n    ..: 38 more,\n}");
+}
+
+// With the debug-truncate feature, the Debug output of a large map is cut off
+#[cfg(feature = "debug-truncate")]
+#[test]
+fn debug_truncate_feature() {
+    let map: FcHashMap<u32, u32, 64> = (0..40).map(|i| (i, i)).collect();
+    assert!(format!("{:?}", map).ends_with(", 31: 31, ..: 8 more}"));
+    assert!(format!("{:#?}", map).contains("len: 40,"));
+}
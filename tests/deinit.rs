@@ -0,0 +1,33 @@
+mod common;
+
+use common::{fill, SEED};
+use fchashmap::FcHashMap;
+use rand_core::SeedableRng;
+use rand_xorshift::XorShiftRng;
+
+// deinit() must hand over the entries in the order of iter(), and leave a usable empty map even
+// if the closure panics
+#[test]
+fn deinit_in_map_order() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcHashMap::<u32, u32, 64>::new();
+    fill(&mut rng, &mut map, 50, u32::MAX, |k| k);
+    map.retain(|k, _| k % 4 != 0);
+    let expected: Vec<_> = map.iter().map(|(k, v)| (*k, *v)).collect();
+    let mut seen = Vec::new();
+    map.deinit(|k, v| seen.push((k, v)));
+    assert_eq!(seen, expected);
+    assert!(map.is_empty());
+
+    for k in 0..10 {
+        map.insert(k, k).unwrap();
+    }
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        map.deinit(|k, _| assert!(k < 5));
+    }));
+    assert!(result.is_err());
+    assert!(map.is_empty());
+    assert_eq!(map.get(&7), None);
+    map.insert(7, 7).unwrap();
+    assert_eq!(map.get(&7), Some(&7));
+}
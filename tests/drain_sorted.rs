@@ -0,0 +1,57 @@
+mod common;
+
+use common::{fill, SEED};
+use fchashmap::FcHashMap;
+use rand_core::SeedableRng;
+use rand_xorshift::XorShiftRng;
+use std::mem::MaybeUninit;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+// drain_sorted_into() into a short slice must move the smallest entries and keep the others
+// reachable
+#[test]
+fn drain_sorted_into_short_slice() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcHashMap::<u32, u32, 64>::new();
+    fill(&mut rng, &mut map, 50, 1000, |key| key + 1);
+    let mut keys: Vec<_> = map.keys().copied().collect();
+    keys.sort_unstable();
+
+    let mut out = [MaybeUninit::<(u32, u32)>::uninit(); 20];
+    assert_eq!(map.drain_sorted_into(&mut out, |a, b| a.0.cmp(b.0)), 20);
+    let drained: Vec<_> = out.iter().map(|e| unsafe { e.assume_init() }.0).collect();
+    assert_eq!(drained, keys[..20]);
+
+    assert_eq!(map.len(), 30);
+    for key in keys[20..].iter() {
+        assert_eq!(map.get(key), Some(&(key + 1)));
+    }
+    for key in keys[..20].iter() {
+        assert_eq!(map.get(key), None);
+    }
+}
+
+// A panic in the comparison of drain_sorted_into() must leave all entries in the map, reachable
+#[test]
+fn drain_sorted_into_panic_keeps_entries() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcHashMap::<u32, u32, 64>::new();
+    fill(&mut rng, &mut map, 50, 1000, |key| key + 1);
+    let keys: Vec<_> = map.keys().copied().collect();
+
+    let mut out = [MaybeUninit::<(u32, u32)>::uninit(); 20];
+    let mut calls = 0;
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        map.drain_sorted_into(&mut out, |a, b| {
+            calls += 1;
+            assert!(calls < 40, "comparison panics");
+            a.0.cmp(b.0)
+        })
+    }));
+    assert!(result.is_err());
+
+    assert_eq!(map.len(), 50);
+    for key in keys.iter() {
+        assert_eq!(map.get(key), Some(&(key + 1)));
+    }
+}
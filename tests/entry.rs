@@ -0,0 +1,47 @@
+mod common;
+
+use common::SEED;
+use fchashmap::{Entry, FcHashMap};
+use rand_core::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use std::collections::HashMap;
+
+// The entry API must behave like the entry API of the std HashMap, except on a full map
+#[test]
+fn entry_like_std() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcHashMap::<u32, u32, 128>::new();
+    let mut std_map = HashMap::new();
+    for _ in 0..20000 {
+        let key = rng.next_u32() % 200;
+        let value = rng.next_u32();
+        match rng.next_u32() % 3 {
+            0 => match map.entry(key) {
+                Ok(Entry::Occupied(entry)) => {
+                    assert_eq!(Some(entry.remove()), std_map.remove(&key));
+                }
+                Ok(Entry::Vacant(entry)) => assert!(!std_map.contains_key(entry.key())),
+                Err(k) => {
+                    assert_eq!(k, key);
+                    assert_eq!(std_map.len(), 128);
+                    assert!(!std_map.contains_key(&key));
+                }
+            },
+            _ => match map.entry(key) {
+                Ok(entry) => {
+                    let v = entry.and_modify(|v| *v ^= value).or_insert(value);
+                    let std_v = std_map
+                        .entry(key)
+                        .and_modify(|v| *v ^= value)
+                        .or_insert(value);
+                    assert_eq!(v, std_v);
+                }
+                Err(_) => assert_eq!(std_map.len(), 128),
+            },
+        }
+        assert_eq!(map.len(), std_map.len());
+    }
+    for (k, v) in std_map.iter() {
+        assert_eq!(map.get(k), Some(v));
+    }
+}
@@ -0,0 +1,24 @@
+use fchashmap::FcHashMap;
+
+// Maps with the same content are equal, whatever the order of insertion or the capacity
+#[test]
+fn equal_content() {
+    let mut a = FcHashMap::<u32, u32, 64>::new();
+    let mut b = FcHashMap::<u32, u32, 128>::new();
+    for i in 0..40 {
+        a.insert(i, i * 3).unwrap();
+        b.insert(39 - i, (39 - i) * 3).unwrap();
+    }
+    for i in 40..60 {
+        b.insert(i, 0).unwrap();
+        b.remove(&i);
+    }
+    assert!(a == b);
+    assert_eq!(a, a.clone());
+
+    b.insert(7, 0).unwrap();
+    assert!(a != b);
+    b.insert(7, 21).unwrap();
+    b.insert(99, 0).unwrap();
+    assert!(a != b);
+}
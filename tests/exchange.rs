@@ -0,0 +1,40 @@
+mod common;
+
+use common::{fill, SEED};
+use fchashmap::FcHashMap;
+use rand_core::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+// exchange_entries() must swap the entries of two maps of different capacities in their order
+// and leave them reachable in both maps
+#[test]
+fn exchange_entries_keeps_order() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut small = FcHashMap::<u32, u32, 64>::new();
+    let mut large = FcHashMap::<u32, u32, 512>::new();
+    for round in 0..50 {
+        let target = rng.next_u32() as usize % 60;
+        fill(&mut rng, &mut small, target, u32::MAX, |k| k);
+        small.truncate(target);
+        let target = rng.next_u32() as usize % (if round % 2 == 0 { 60 } else { 400 });
+        fill(&mut rng, &mut large, target, u32::MAX, |k| k);
+        large.truncate(target);
+
+        let small_before: Vec<_> = small.iter().map(|(k, v)| (*k, *v)).collect();
+        let large_before: Vec<_> = large.iter().map(|(k, v)| (*k, *v)).collect();
+        if large.len() > 64 {
+            assert!(small.exchange_entries(&mut large).is_err());
+            assert!(small.iter().map(|(k, v)| (*k, *v)).eq(small_before.iter().copied()));
+            continue;
+        }
+        small.exchange_entries(&mut large).unwrap();
+        assert!(small.iter().map(|(k, v)| (*k, *v)).eq(large_before.iter().copied()));
+        assert!(large.iter().map(|(k, v)| (*k, *v)).eq(small_before.iter().copied()));
+        for (k, v) in small.iter().chain(large.iter()) {
+            assert_eq!(small.get(k).or_else(|| large.get(k)), Some(v));
+        }
+        for (k, _) in large_before.iter() {
+            assert_eq!(small.get(k), Some(k));
+        }
+    }
+}
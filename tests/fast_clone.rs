@@ -0,0 +1,27 @@
+mod common;
+
+use common::SEED;
+use fchashmap::FcHashMap;
+use rand_core::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+// fast_clone() must give the same order and lookups as clone()
+#[test]
+fn fast_clone_like_clone() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcHashMap::<u32, u32, 1024>::new();
+    for round in 0..3000 {
+        let key = rng.next_u32() % 1500;
+        if round % 4 == 0 {
+            map.remove(&key);
+        } else {
+            let _ = map.insert(key, rng.next_u32());
+        }
+    }
+    let cloned = map.clone();
+    let copied = map.fast_clone();
+    assert!(copied.iter().eq(cloned.iter()));
+    for (k, v) in map.iter() {
+        assert_eq!(copied.get(k), Some(v));
+    }
+}
@@ -0,0 +1,14 @@
+use fchashmap::FcHashMap;
+
+// The panic of a full map names its capacity, and the position of the
+// rejected entry with the panic-position feature
+#[test]
+fn collect_into_full_map() {
+    let result =
+        std::panic::catch_unwind(|| (0..5_u32).map(|i| (i, i)).collect::<FcHashMap<_, _, 4>>());
+    let message = *result.unwrap_err().downcast::<String>().unwrap();
+    assert!(message.contains("(4/4)"));
+    if cfg!(all(feature = "panic-position", debug_assertions)) {
+        assert!(message.ends_with("entry 4 of the iterator"));
+    }
+}
@@ -0,0 +1,54 @@
+use fchashmap::FcHashMap;
+use std::cell::{Cell, RefCell};
+
+// Values with interior mutability can be changed through shared references, several at a time
+#[test]
+fn refcell_values() {
+    let mut map = FcHashMap::<u32, RefCell<Vec<u32>>, 16>::new();
+    for key in 0..4 {
+        map.insert(key, RefCell::new(Vec::new())).unwrap();
+    }
+
+    let map = &map;
+    let mut first = map.get(&0).unwrap().borrow_mut();
+    let mut second = map.get(&1).unwrap().borrow_mut();
+    first.push(1);
+    second.push(2);
+    // Lookups of other keys work while the values are borrowed
+    assert_eq!(map.get(&2).unwrap().borrow().len(), 0);
+    assert!(map.get(&0).unwrap().try_borrow().is_err());
+    drop(first);
+    drop(second);
+
+    for (key, value) in map.iter() {
+        value.borrow_mut().push(*key * 10);
+    }
+    assert_eq!(*map.get(&1).unwrap().borrow(), vec![2, 10]);
+}
+
+// Cells keep their content through inserts and removals of other keys, which move the entries
+#[test]
+fn cells_survive_moves() {
+    let mut map = FcHashMap::<u32, Cell<u32>, 64>::new();
+    for key in 0..40 {
+        map.insert(key, Cell::new(0)).unwrap();
+    }
+    for round in 0..10 {
+        for key in 0..40 {
+            map.update_cell(&key, |n| n + key);
+        }
+        map.remove(&(round * 3));
+        map.insert(round * 3, Cell::new(round)).unwrap();
+    }
+    for key in 0..40 {
+        // A key that was inserted again in round r started with r and was updated in the
+        // following rounds
+        let expected = if key % 3 == 0 && key < 30 {
+            key / 3 + (9 - key / 3) * key
+        } else {
+            key * 10
+        };
+        assert_eq!(map.get_cell(&key), Some(expected), "key {}", key);
+    }
+    assert!(!map.set_cell(&100, 1));
+}
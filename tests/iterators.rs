@@ -0,0 +1,98 @@
+use core::iter::FusedIterator;
+use fchashmap::{FcHashMap, Keys, Values, ValuesMut};
+
+// Check that an iterator reports its exact length at every step and stays exhausted
+fn check_exact<I: ExactSizeIterator + FusedIterator>(mut iter: I, len: usize) {
+    for remaining in (0..=len).rev() {
+        assert_eq!(iter.len(), remaining);
+        assert_eq!(iter.size_hint(), (remaining, Some(remaining)));
+        assert_eq!(iter.next().is_some(), remaining > 0);
+    }
+    assert!(iter.next().is_none());
+    assert_eq!(iter.len(), 0);
+}
+
+#[test]
+fn exact_size() {
+    let mut map: FcHashMap<u32, u32, 32> = (0..20).map(|i| (i, i * 2)).collect();
+    map.remove(&7);
+    let len = map.len();
+
+    check_exact(map.iter(), len);
+    check_exact(map.iter_mut(), len);
+    check_exact(map.iter_indexed(), len);
+    check_exact(map.keys(), len);
+    check_exact(map.values(), len);
+    check_exact(map.values_mut(), len);
+    check_exact(map.clone().into_iter(), len);
+    check_exact(map.clone().into_keys(), len);
+    check_exact(map.into_values(), len);
+}
+
+// Iterating from both ends must meet in the middle and yield every entry once
+#[test]
+fn double_ended() {
+    let mut map: FcHashMap<u32, u32, 32> = (0..9).map(|i| (i, i * 2)).collect();
+    let forward: Vec<_> = map.iter().map(|(k, v)| (*k, *v)).collect();
+
+    let mut backward: Vec<_> = map.iter().rev().map(|(k, v)| (*k, *v)).collect();
+    backward.reverse();
+    assert_eq!(backward, forward);
+
+    let mut iter = map.iter();
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+    while let Some((k, _)) = iter.next() {
+        front.push(*k);
+        if let Some((k, _)) = iter.next_back() {
+            back.push(*k);
+        }
+    }
+    back.reverse();
+    front.extend(back);
+    assert_eq!(front, forward.iter().map(|(k, _)| *k).collect::<Vec<_>>());
+
+    for (_, v) in map.iter_mut().rev().take(3) {
+        *v = 0;
+    }
+    assert_eq!(map.values().filter(|v| **v == 0).count(), 4);
+
+    let into: Vec<_> = map.into_iter().rev().map(|(k, _)| k).collect();
+    assert_eq!(into, (0..9).rev().collect::<Vec<_>>());
+}
+
+// A task that walks over a map step by step, holding the iterators between the steps
+struct Walk<'a> {
+    keys: Keys<'a, u32, u32>,
+    values: Values<'a, u32, u32>,
+}
+
+impl<'a> Walk<'a> {
+    fn step(&mut self) -> Option<(u32, u32)> {
+        Some((*self.keys.next()?, *self.values.next()?))
+    }
+}
+
+#[test]
+fn named_iterators() {
+    let mut map: FcHashMap<u32, u32, 16> = (0..5).map(|i| (i, i * 3)).collect();
+
+    let mut walk = Walk {
+        keys: map.keys(),
+        values: map.values(),
+    };
+    let mut steps = Vec::new();
+    while let Some(step) = walk.step() {
+        steps.push(step);
+    }
+    assert_eq!(steps, map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>());
+
+    let newest: Vec<_> = map.keys().rev().take(2).copied().collect();
+    assert_eq!(newest, vec![4, 3]);
+
+    let values_mut: ValuesMut<'_, u32, u32> = map.values_mut();
+    for v in values_mut.rev() {
+        *v += 1;
+    }
+    assert_eq!(map.values().copied().collect::<Vec<_>>(), vec![1, 4, 7, 10, 13]);
+}
@@ -0,0 +1,43 @@
+#![cfg(all(feature = "key-diagnostics", debug_assertions))]
+mod common;
+
+use common::{fill, SEED};
+use fchashmap::key::Mixed;
+use fchashmap::FcHashMap;
+use hash32::{Hash, Hasher};
+use rand_core::SeedableRng;
+use rand_xorshift::XorShiftRng;
+
+// A key that feeds only 4 bits of entropy into the hasher
+#[derive(Debug, PartialEq, Eq)]
+struct PoorKey(u32);
+
+impl Hash for PoorKey {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write(&[(self.0 & 0xf) as u8]);
+    }
+}
+
+#[test]
+fn random_keys_pass() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcHashMap::<u32, u32, 4096>::new();
+    fill(&mut rng, &mut map, 2048, u32::MAX, |_| 0);
+}
+
+#[test]
+fn mixed_keys_pass() {
+    let mut map = FcHashMap::<_, _, 4096>::new();
+    for i in 0..2048_u32 {
+        map.insert(Mixed(i << 20), 0).unwrap();
+    }
+}
+
+#[test]
+#[should_panic(expected = "badly distributed")]
+fn skewed_keys_panic() {
+    let mut map = FcHashMap::<_, _, 4096>::new();
+    for i in 0..2048_u32 {
+        map.insert(PoorKey(i), 0).unwrap();
+    }
+}
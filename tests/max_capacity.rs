@@ -0,0 +1,45 @@
+mod common;
+
+use common::SEED;
+use fchashmap::FcHashMap;
+use rand_core::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use std::collections::HashMap;
+
+const MAP_SIZE: usize = 32768;
+
+// The largest capacity must address every entry, up to a completely full map. Indices that were
+// truncated anywhere would make entries unreachable or point to wrong buckets.
+#[test]
+fn fill_max_capacity() {
+    // The map is built on the stack, which is too small for it in debug builds
+    std::thread::Builder::new()
+        .stack_size(32 << 20)
+        .spawn(fill)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+fn fill() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = Box::new(FcHashMap::<u32, u32, MAP_SIZE>::new());
+    let mut std_map = HashMap::new();
+    while map.len() < MAP_SIZE {
+        let (k, v) = (rng.next_u32(), rng.next_u32());
+        assert_eq!(map.insert(k, v), Ok(std_map.insert(k, v)));
+    }
+    assert!(map.insert(rng.next_u32(), 0).is_err());
+    for (k, v) in std_map.iter() {
+        assert_eq!(map.get(k), Some(v));
+    }
+
+    let keys: Vec<_> = std_map.keys().copied().collect();
+    for k in keys.iter().step_by(2) {
+        assert_eq!(map.remove(k), std_map.remove(k));
+    }
+    assert_eq!(map.len(), MAP_SIZE / 2);
+    for k in keys.iter() {
+        assert_eq!(map.get(k), std_map.get(k));
+    }
+}
@@ -1,3 +1,6 @@
+mod common;
+
+use common::SEED;
 use fchashmap::FcHashMap;
 use rand_xorshift::XorShiftRng;
 use rand_core::{RngCore, SeedableRng};
@@ -10,8 +13,6 @@ struct MonteCarlo {
 }
 
 const MAP_SIZE: usize = 16384;
-const SEED: u64 = 1234567890987654321;
-
 
 impl MonteCarlo {
     fn new() -> Self {
@@ -36,7 +37,7 @@ impl MonteCarlo {
             },
             Err(e) => {
                 println!("Error 2, len {}, key {}, value {}, e{:?}, r_std {:?}", self.fc_hashmap.len(), key, value, e, r_std);
-                assert!(false);
+                panic!();
             },
         }
     }
@@ -86,7 +87,7 @@ impl MonteCarlo {
 
         let mut rng = XorShiftRng::seed_from_u64(SEED);
         loop {
-            if self.fc_hashmap.len() == 0 {
+            if self.fc_hashmap.is_empty() {
                 break;
             }
             self.remove(&mut rng);
@@ -0,0 +1,89 @@
+mod common;
+
+use common::{fill, SEED};
+use fchashmap::{FcHashMap, MoveError};
+use rand_core::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+// extract_to() into a map that gets full must leave the entries that didn't fit at the back of
+// the map and keep all entries of both maps reachable
+#[test]
+fn extract_to_keeps_rest_at_back() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    for round in 0..50 {
+        let mut map = FcHashMap::<u32, u32, 64>::new();
+        fill(&mut rng, &mut map, 50, 1000, |key| key + 1);
+        let mut other = FcHashMap::<u32, u32, 16>::new();
+        for _ in 0..round % 8 {
+            let key = rng.next_u32() % 1000;
+            let _ = other.insert(key, 0);
+        }
+        let before = map.clone();
+        let other_before = other.clone();
+
+        let result = map.extract_to(&mut other, |k, _| k % 3 != 0);
+        let matching = before.keys().filter(|k| *k % 3 != 0).count();
+        let not_moved = match result {
+            Ok(moved) => {
+                assert_eq!(moved, matching);
+                0
+            }
+            Err(MoveError { moved, not_moved }) => {
+                assert_eq!(moved + not_moved, matching);
+                assert_eq!(other.len(), 16);
+                not_moved
+            }
+        };
+
+        for (k, v) in before.iter() {
+            let in_map = map.get(k);
+            let in_other = other.get(k);
+            if k % 3 == 0 {
+                assert_eq!(in_map, Some(v));
+            } else {
+                assert!(in_map.is_some() != (in_other == Some(v)));
+            }
+        }
+        for (k, v) in other_before.iter() {
+            assert!(other.get(k) == Some(v) || before.contains_key(k));
+        }
+        for (k, v) in map.iter().rev().take(not_moved) {
+            assert!(k % 3 != 0);
+            assert_eq!(other.get(k), None);
+            assert_eq!(before.get(k), Some(v));
+        }
+        for k in map.keys().rev().skip(not_moved) {
+            assert_eq!(k % 3, 0);
+        }
+    }
+}
+
+// merge() into a map that gets full must leave exactly the entries that didn't fit in the other
+// map and combine the values of common keys
+#[test]
+fn merge_leaves_rest_in_other() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    for _ in 0..50 {
+        let mut map = FcHashMap::<u32, u32, 32>::new();
+        let mut other = FcHashMap::<u32, u32, 32>::new();
+        fill(&mut rng, &mut map, 20, 64, |_| 1);
+        fill(&mut rng, &mut other, 20, 64, |_| 10);
+        let before = map.clone();
+        let other_before = other.clone();
+
+        let result = map.merge(&mut other, |sum, v| *sum += v);
+        let not_moved = result.err().map_or(0, |err| err.not_moved);
+        assert_eq!(other.len(), not_moved);
+
+        for (k, _) in other_before.iter() {
+            match (before.contains_key(k), other.contains_key(k)) {
+                (true, _) => assert_eq!(map.get(k), Some(&11)),
+                (false, true) => assert_eq!(map.get(k), None),
+                (false, false) => assert_eq!(map.get(k), Some(&10)),
+            }
+        }
+        for (k, _) in before.iter() {
+            assert!(map.contains_key(k));
+        }
+    }
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "net")]
+use fchashmap::net::{FlowKey, IpKey, Ipv4Key, Ipv6Key};
+use hash32::{BuildHasher, BuildHasherDefault, FnvHasher, Hash, Hasher};
+use std::collections::HashSet;
+
+// The 12 bits of the hash value that select the place in a map with a capacity of 4096
+fn place<K: Hash>(key: &K) -> u32 {
+    let mut h = BuildHasherDefault::<FnvHasher>::default().build_hasher();
+    key.hash(&mut h);
+    h.finish() & 0xfff
+}
+
+// 1024 keys on 4096 places occupy about 900 different places if they are spread randomly
+fn check_spread<K: Hash>(keys: impl Iterator<Item = K>) {
+    let places: HashSet<_> = keys.map(|key| place(&key)).collect();
+    assert!(places.len() > 850, "only {} places", places.len());
+}
+
+#[test]
+fn addresses_of_a_subnet_spread() {
+    check_spread((0..1024_u32).map(|i| Ipv4Key([10, 0, (i >> 8) as u8, i as u8])));
+    check_spread((0..1024_u32).map(|i| {
+        let mut addr = [0xfe, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        addr[14..].copy_from_slice(&(i as u16).to_be_bytes());
+        IpKey::V6(Ipv6Key(addr))
+    }));
+}
+
+#[test]
+fn flows_of_a_client_spread() {
+    check_spread(
+        (0..1024_u16).map(|port| FlowKey::v4([192, 168, 0, 2], 49152 + port, [1, 1, 1, 1], 443, 6)),
+    );
+    check_spread((0..1024_u32).map(|i| {
+        FlowKey::v4([192, 168, (i >> 8) as u8, i as u8], 5000, [192, 168, 0, 1], 53, FlowKey::UDP)
+    }));
+}
+
+#[test]
+fn reversed_flow() {
+    let flow = FlowKey::v4([10, 0, 0, 1], 1234, [10, 0, 0, 2], 80, FlowKey::TCP);
+    assert_ne!(flow, flow.reversed());
+    assert_eq!(flow, flow.reversed().reversed());
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::SEED;
+use fchashmap::FcOrderedHashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use rand_core::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+// The order of the entries must always be the order of insertion, compared against a Vec
+#[test]
+fn insertion_order() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcOrderedHashMap::<u32, u32, 256>::new();
+    let mut model: Vec<(u32, u32)> = Vec::new();
+    for round in 0..5000 {
+        let key = rng.next_u32() % 300;
+        if round % 3 == 0 {
+            let position = model.iter().position(|(k, _)| *k == key);
+            let expected = position.map(|i| model.remove(i));
+            assert_eq!(map.remove_entry(&key), expected);
+        } else {
+            let value = rng.next_u32();
+            let full = model.len() == 256;
+            match model.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, v)) => {
+                    let old = std::mem::replace(v, value);
+                    assert_eq!(map.insert(key, value), Ok(Some(old)));
+                }
+                None if !full => {
+                    assert_eq!(map.insert(key, value), Ok(None));
+                    model.push((key, value));
+                }
+                None => assert_eq!(map.insert(key, value), Err((key, value))),
+            }
+        }
+
+        assert!(map.iter().map(|(k, v)| (*k, *v)).eq(model.iter().copied()));
+        for (idx, (k, v)) in model.iter().enumerate() {
+            assert_eq!(map.get(k), Some(v));
+            assert_eq!(map.index_of(k), Some(idx));
+        }
+    }
+}
+
+// retain() must keep the order of the remaining entries and leave them reachable
+#[test]
+fn retain_keeps_order() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcOrderedHashMap::<u32, u32, 128>::new();
+    let mut model: Vec<u32> = Vec::new();
+    while model.len() < 100 {
+        let key = rng.next_u32();
+        if map.insert(key, key).unwrap().is_none() {
+            model.push(key);
+        }
+    }
+    map.retain(|k, _| k % 3 != 0);
+    model.retain(|k| k % 3 != 0);
+
+    assert!(map.keys().eq(model.iter()));
+    for k in model.iter() {
+        assert_eq!(map.get(k), Some(k));
+    }
+    assert_eq!(map.remove(&model[0]), Some(model[0]));
+    assert!(map.keys().eq(model[1..].iter()));
+}
+
+// A panic in the predicate of retain() must leave a consistent map behind
+#[test]
+fn retain_panic_keeps_map_consistent() {
+    let mut map = FcOrderedHashMap::<u32, u32, 64>::new();
+    for key in 0..50 {
+        map.insert(key, key).unwrap();
+    }
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        map.retain(|k, _| {
+            assert!(*k != 30, "predicate panics");
+            k % 2 == 0
+        })
+    }));
+    assert!(result.is_err());
+
+    for (k, v) in map.iter() {
+        assert_eq!(map.get(k), Some(v));
+    }
+    for key in 0..50 {
+        assert_eq!(map.contains_key(&key), map.keys().any(|k| *k == key));
+    }
+    assert!(map.len() >= 25);
+    map.insert(100, 100).unwrap();
+    assert_eq!(map.remove(&100), Some(100));
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use common::{fill, SEED};
+use fchashmap::FcHashMap;
+use rand_core::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+// keys(), values() and entries() must correspond index for index after any sequence of inserts
+// and removals
+#[test]
+fn entries_as_pairs() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcHashMap::<u32, u32, 256>::new();
+    for round in 0..2000 {
+        let key = rng.next_u32() % 512;
+        if round % 3 == 0 {
+            map.remove(&key);
+        } else {
+            let _ = map.insert(key, rng.next_u32());
+        }
+
+        let zipped: Vec<_> = map.keys().zip(map.values()).collect();
+        let entries: Vec<_> = map.entries().collect();
+        assert_eq!(zipped, entries);
+        for (k, v) in entries {
+            assert_eq!(map.get(k), Some(v));
+        }
+    }
+}
+
+// Deferred removals must leave the remaining entries reachable
+#[test]
+fn deferred_removals() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcHashMap::<u32, u32, 256>::new();
+    let mut std_map = std::collections::HashMap::new();
+    for _ in 0..50 {
+        fill(&mut rng, &mut map, 220, 1024, |k| k.wrapping_mul(0x9e37_79b9));
+        std_map.extend(map.iter().map(|(k, v)| (*k, *v)));
+        let keys: Vec<_> = map.keys().copied().collect();
+        for key in keys {
+            if rng.next_u32() % 3 == 0 {
+                assert!(map.defer_remove(&key));
+                std_map.remove(&key);
+            }
+        }
+        map.flush_removals();
+        assert_eq!(map.len(), std_map.len());
+        for (k, v) in std_map.iter() {
+            assert_eq!(map.get(k), Some(v));
+        }
+    }
+}
+
+// Transformed values keep the order and stay reachable by their keys, with and without consuming
+// the map
+#[test]
+fn map_values_keeps_layout() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcHashMap::<u32, u32, 512>::new();
+    fill(&mut rng, &mut map, 450, u32::MAX, |k| k / 2);
+    let keys: Vec<_> = map.keys().copied().collect();
+    let halves = map.map_values_ref(|k, v| *k - *v);
+    let map = map.map_values(|k, v| u64::from(*k) + u64::from(v));
+    assert_eq!(map.keys().copied().collect::<Vec<_>>(), keys);
+    assert_eq!(halves.keys().copied().collect::<Vec<_>>(), keys);
+    for k in keys {
+        assert_eq!(map.get(&k), Some(&(u64::from(k) + u64::from(k / 2))));
+        assert_eq!(halves.get(&k), Some(&(k - k / 2)));
+    }
+}
+
+// retain() must visit every entry once and leave the retained entries reachable
+#[test]
+fn retain_like_std() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcHashMap::<u32, u32, 256>::new();
+    let mut std_map = std::collections::HashMap::new();
+    for _ in 0..50 {
+        fill(&mut rng, &mut map, 230, 1024, |k| k.wrapping_mul(0x9e37_79b9));
+        std_map.extend(map.iter().map(|(k, v)| (*k, *v)));
+        let mask = 1 << (rng.next_u32() % 4);
+        let mut visited = 0;
+        map.retain(|_, v| {
+            visited += 1;
+            *v = v.wrapping_add(1);
+            *v & mask == 0
+        });
+        std_map.retain(|_, v| {
+            *v = v.wrapping_add(1);
+            *v & mask == 0
+        });
+        assert_eq!(visited, 230);
+        assert_eq!(map.len(), std_map.len());
+        for (k, v) in std_map.iter() {
+            assert_eq!(map.get(k), Some(v));
+        }
+    }
+}
+
+// An extract_if() iterator dropped half way must leave a consistent map
+#[test]
+fn extract_if_partially_consumed() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcHashMap::<u32, u32, 256>::new();
+    let mut std_map = std::collections::HashMap::new();
+    for _ in 0..50 {
+        fill(&mut rng, &mut map, 230, 1024, |k| k.wrapping_mul(0x9e37_79b9));
+        std_map.extend(map.iter().map(|(k, v)| (*k, *v)));
+        let n = (rng.next_u32() % 40) as usize;
+        for (k, v) in map.extract_if(|_, v| *v % 3 == 0).take(n) {
+            assert_eq!(std_map.remove(&k), Some(v));
+        }
+        assert_eq!(map.len(), std_map.len());
+        for (k, v) in std_map.iter() {
+            assert_eq!(map.get(k), Some(v));
+        }
+    }
+}
+
+// Consuming iteration moves the entries out in the order of the map
+#[test]
+fn into_iter_in_order() {
+    let map: FcHashMap<u32, String, 64> = (0..40).map(|i| (i * 7, i.to_string())).collect();
+    let expected: Vec<_> = map.iter().map(|(k, v)| (*k, v.clone())).collect();
+    let moved: Vec<(u32, String)> = map.into_iter().collect();
+    assert_eq!(moved, expected);
+}
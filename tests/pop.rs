@@ -0,0 +1,29 @@
+mod common;
+
+use common::SEED;
+use fchashmap::FcHashMap;
+use rand_core::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+// pop() must remove the entries in the reverse order of iter() and keep the others reachable
+#[test]
+fn pop_in_reverse_order() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcHashMap::<u32, u32, 128>::new();
+    for round in 0..200 {
+        let key = rng.next_u32() % 256;
+        if round % 4 == 0 {
+            map.remove(&key);
+        } else {
+            let _ = map.insert(key, key * 2);
+        }
+    }
+    let mut entries: Vec<_> = map.iter().map(|(k, v)| (*k, *v)).collect();
+    while let Some(last) = entries.pop() {
+        assert_eq!(map.pop(), Some(last));
+        for (k, v) in entries.iter() {
+            assert_eq!(map.get(k), Some(v));
+        }
+    }
+    assert_eq!(map.pop(), None);
+}
@@ -0,0 +1,99 @@
+use fchashmap::FcHashMap;
+use std::collections::HashMap;
+
+// Recorded operation sequences that stress the removal of entries. remove() moves the last bucket
+// into the place of the removed one (swap_pop), fixes the index pointing to the moved bucket and
+// then shifts the following indices back. The sequences are chosen so that the moved bucket and
+// the removed entry share a cluster of colliding keys, clusters wrap around the end of the hash
+// table and the map is completely full.
+//
+// With a capacity of 8, keys with the same value modulo 8 want the same place in the hash table:
+// 5, 13, 21 and 29 want place 0, 4 and 12 place 1, 3, 11 and 19 place 6, 2, 10 and 18 place 7.
+
+const CAP: usize = 8;
+
+#[derive(Clone, Copy, Debug)]
+enum Op {
+    Insert(u32),
+    Remove(u32),
+}
+use Op::*;
+
+const CORPUS: &[(&str, &[Op])] = &[
+    (
+        "swap partner displaced in the same cluster",
+        &[Insert(5), Insert(13), Insert(21), Insert(4), Remove(5), Remove(13)],
+    ),
+    (
+        "swap partner behind the removed entry",
+        &[Insert(4), Insert(5), Insert(13), Insert(12), Remove(4), Remove(12), Remove(5)],
+    ),
+    (
+        "cluster wrapping around the end of the table",
+        &[Insert(2), Insert(10), Insert(18), Insert(5), Insert(13), Remove(2), Remove(18)],
+    ),
+    (
+        "removal of the last bucket without swap",
+        &[Insert(3), Insert(11), Insert(19), Remove(19), Remove(3), Insert(27), Remove(11)],
+    ),
+    (
+        "full map, removals in mixed order",
+        &[
+            Insert(5),
+            Insert(13),
+            Insert(21),
+            Insert(29),
+            Insert(4),
+            Insert(12),
+            Insert(2),
+            Insert(10),
+            Remove(29),
+            Remove(5),
+            Remove(10),
+            Insert(18),
+            Remove(4),
+            Remove(21),
+            Insert(37),
+            Remove(13),
+            Remove(2),
+            Remove(12),
+            Remove(18),
+            Remove(37),
+        ],
+    ),
+];
+
+// Replay a sequence against FcHashMap and std HashMap. After every operation, both maps must hold
+// the same entries and every entry must be reachable by its key.
+fn replay(name: &str, ops: &[Op]) {
+    let mut map = FcHashMap::<u32, u32, CAP>::new();
+    let mut std_map = HashMap::new();
+    for (step, op) in ops.iter().enumerate() {
+        match *op {
+            Insert(key) => {
+                let value = key * 10 + step as u32;
+                assert_eq!(
+                    map.insert(key, value),
+                    Ok(std_map.insert(key, value)),
+                    "{}: step {}",
+                    name,
+                    step
+                );
+            }
+            Remove(key) => {
+                assert_eq!(map.remove(&key), std_map.remove(&key), "{}: step {}", name, step);
+            }
+        }
+        assert_eq!(map.len(), std_map.len(), "{}: step {}", name, step);
+        for (key, value) in std_map.iter() {
+            assert_eq!(map.get(key), Some(value), "{}: step {}", name, step);
+        }
+    }
+}
+
+#[test]
+fn removal_corpus() {
+    for (name, ops) in CORPUS {
+        replay(name, ops);
+    }
+}
@@ -0,0 +1,124 @@
+#![cfg(feature = "std")]
+mod common;
+
+use common::SEED;
+use fchashmap::oplog::{OpKind, OpRecord, Outcome};
+use fchashmap::replay::{replay, ReplayError};
+use rand_core::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use std::collections::HashSet;
+
+// The 32 bit hash value of a key, as calculated by the map
+#[cfg(feature = "recorder")]
+fn fnv<K: hash32::Hash>(key: &K) -> u32 {
+    use hash32::{BuildHasher, BuildHasherDefault, FnvHasher, Hasher};
+    let mut h = BuildHasherDefault::<FnvHasher>::default().build_hasher();
+    key.hash(&mut h);
+    h.finish()
+}
+
+// Build a random log with correct outcomes, as a target with a map of capacity CAP records it
+fn random_log<const CAP: usize>(steps: usize) -> Vec<u8> {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut keys = HashSet::new();
+    let mut log = Vec::new();
+    for _ in 0..steps {
+        // Few distinct keys, so that the map runs full now and then
+        let key_hash = rng.next_u32() % (CAP as u32 * 2);
+        let (kind, outcome) = match rng.next_u32() % 3 {
+            0 if keys.len() == CAP => (OpKind::Insert, Outcome::Rejected),
+            0 if keys.insert(key_hash) => (OpKind::Insert, Outcome::Miss),
+            0 => (OpKind::Insert, Outcome::Hit),
+            1 if keys.remove(&key_hash) => (OpKind::Remove, Outcome::Hit),
+            1 => (OpKind::Remove, Outcome::Miss),
+            _ if keys.contains(&key_hash) => (OpKind::Get, Outcome::Hit),
+            _ => (OpKind::Get, Outcome::Miss),
+        };
+        let record = OpRecord {
+            kind,
+            key_hash,
+            outcome,
+        };
+        log.extend_from_slice(&record.to_bytes());
+    }
+    log
+}
+
+#[test]
+fn replay_consistent_log() {
+    let log = random_log::<64>(10000);
+    let map = replay::<64>(&log).unwrap();
+    assert!(map.len() <= 64);
+}
+
+// A flipped outcome, as from a target whose map went wrong, is found at its step
+#[test]
+fn replay_finds_divergence() {
+    let mut log = random_log::<64>(1000);
+    let step = 700;
+    log[step * 5] ^= 0x10;
+    match replay::<64>(&log) {
+        Err(ReplayError::Diverged(d)) => assert_eq!(d.step, step),
+        Err(ReplayError::Malformed { step: s }) => assert_eq!(s, step),
+        Ok(_) => panic!("divergence not found"),
+    }
+}
+
+// A log recorded on a real map replays to the same hash table
+#[cfg(feature = "recorder")]
+#[test]
+fn replay_recorded_log() {
+    use fchashmap::oplog::Recorder;
+    use fchashmap::FcHashMap;
+
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut buf = vec![0; 5 * 5000];
+    let mut recorder = Recorder::new(&mut buf);
+    let mut map = FcHashMap::<u16, u32, 128>::new();
+    for _ in 0..5000 {
+        let key = (rng.next_u32() % 300) as u16;
+        match rng.next_u32() % 3 {
+            0 => drop(recorder.insert(&mut map, key, rng.next_u32())),
+            1 => drop(recorder.remove(&mut map, &key)),
+            _ => drop(recorder.get(&map, &key)),
+        }
+    }
+    assert_eq!(recorder.overwritten(), 0);
+    let (log, rest) = recorder.as_slices();
+    assert!(rest.is_empty());
+
+    let replayed = replay::<128>(log).unwrap();
+    assert_eq!(replayed.len(), map.len());
+    // Same operations, same order of the entries
+    let hashes: Vec<u32> = map.keys().map(fnv).collect();
+    assert_eq!(replayed.keys().copied().collect::<Vec<_>>(), hashes);
+}
+
+// The ring buffer keeps the latest records in chronological order
+#[cfg(feature = "recorder")]
+#[test]
+fn recorder_wraps_around() {
+    use fchashmap::oplog::{records, Recorder};
+    use fchashmap::FcHashMap;
+
+    let mut buf = [0; 5 * 4 + 3];
+    let mut recorder = Recorder::new(&mut buf);
+    let mut map = FcHashMap::<u32, (), 16>::new();
+    for key in 0..6 {
+        recorder.insert(&mut map, key, ()).unwrap();
+    }
+    assert_eq!(recorder.overwritten(), 2);
+    let (first, second) = recorder.as_slices();
+    let log: Vec<u8> = first.iter().chain(second.iter()).copied().collect();
+    let hashes: Vec<u32> = records(&log).map(|r| r.unwrap().key_hash).collect();
+    let mut expected = FcHashMap::<u32, (), 16>::new();
+    let mut expected_recorder_buf = [0; 5 * 6];
+    let mut expected_recorder = Recorder::new(&mut expected_recorder_buf);
+    for key in 0..6 {
+        expected_recorder.insert(&mut expected, key, ()).unwrap();
+    }
+    let all: Vec<u32> = records(expected_recorder.as_slices().0)
+        .map(|r| r.unwrap().key_hash)
+        .collect();
+    assert_eq!(hashes, all[2..]);
+}
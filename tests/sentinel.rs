@@ -0,0 +1,53 @@
+use fchashmap::FcHashMap;
+use hash32::{BuildHasher, BuildHasherDefault, FnvHasher, Hash, Hasher};
+
+// Calculate the 32 bit hash the map calculates for a key
+fn fnv<T: Hash>(key: &T) -> u32 {
+    let mut h = BuildHasherDefault::<FnvHasher>::default().build_hasher();
+    key.hash(&mut h);
+    h.finish()
+}
+
+// Find a key whose hash has the given low 16 bits
+fn key_with_low_hash(low: u32) -> u32 {
+    (0..).find(|key| fnv(key) & 0xffff == low).unwrap()
+}
+
+fn check_keys(keys: &[u32]) {
+    let mut map = FcHashMap::<u32, usize, 16>::new();
+    for (value, key) in keys.iter().enumerate() {
+        assert_eq!(map.insert(*key, value), Ok(None));
+    }
+    assert_eq!(map.len(), keys.len());
+    for (value, key) in keys.iter().enumerate() {
+        assert_eq!(map.get(key), Some(&value));
+    }
+    for (value, key) in keys.iter().enumerate() {
+        assert_eq!(map.remove(key), Some(value));
+        assert_eq!(map.get(key), None);
+    }
+    assert!(map.is_empty());
+}
+
+#[test]
+fn zero_and_max_keys() {
+    check_keys(&[0, u32::MAX, 1, u32::MAX - 1, 0x8000, 0x8000_0000]);
+
+    let mut map = FcHashMap::<[u8; 8], u8, 4>::new();
+    map.insert([0; 8], 1).unwrap();
+    map.insert([0xff; 8], 2).unwrap();
+    assert_eq!(map.get(&[0; 8]), Some(&1));
+    assert_eq!(map.get(&[0xff; 8]), Some(&2));
+}
+
+#[test]
+fn keys_with_hash_next_to_the_empty_marker() {
+    let keys = [
+        key_with_low_hash(0x8000),
+        key_with_low_hash(0x7fff),
+        key_with_low_hash(0x8001),
+        key_with_low_hash(0x0000),
+        key_with_low_hash(0xffff),
+    ];
+    check_keys(&keys);
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use common::SEED;
+use fchashmap::FcStableHashMap;
+use rand_core::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use std::collections::HashMap;
+
+// A value must keep its address from its insert until its removal, whatever happens to the other
+// keys, and free slots must be reused
+#[test]
+fn values_keep_their_address() {
+    let mut rng = XorShiftRng::seed_from_u64(SEED);
+    let mut map = FcStableHashMap::<u32, u64, 128>::new();
+    let mut model: HashMap<u32, (u64, *const u64)> = HashMap::new();
+    for round in 0..20000 {
+        let key = rng.next_u32() % 200;
+        if round % 3 == 0 {
+            assert_eq!(map.remove(&key), model.remove(&key).map(|(v, _)| v));
+        } else {
+            let value = u64::from(rng.next_u32());
+            if let Some((v, _)) = model.get_mut(&key) {
+                let old = std::mem::replace(v, value);
+                assert_eq!(map.insert(key, value), Ok(Some(old)));
+            } else if model.len() < 128 {
+                assert_eq!(map.insert(key, value), Ok(None));
+                model.insert(key, (value, map.get(&key).unwrap()));
+            } else {
+                assert_eq!(map.insert(key, value), Err((key, value)));
+            }
+        }
+
+        assert_eq!(map.len(), model.len());
+        for (k, (v, addr)) in model.iter() {
+            let value = map.get(k).unwrap();
+            assert_eq!(value, v);
+            assert_eq!(value as *const u64, *addr);
+        }
+    }
+
+    map.clear();
+    assert!(map.is_empty());
+    for key in 0..128 {
+        map.insert(key, 0).unwrap();
+    }
+    assert_eq!(map.insert(128, 0), Err((128, 0)));
+}
+
+// free_slots() must count the freed slots and highest_used_slot() must follow the used slots
+#[test]
+fn slot_statistics() {
+    let mut map = FcStableHashMap::<u32, u32, 16>::new();
+    assert_eq!((map.free_slots(), map.highest_used_slot()), (0, None));
+    for key in 0..10 {
+        map.insert(key, key).unwrap();
+    }
+    assert_eq!((map.free_slots(), map.highest_used_slot()), (0, Some(9)));
+
+    map.remove(&3);
+    map.remove(&9);
+    map.remove(&8);
+    assert_eq!((map.free_slots(), map.highest_used_slot()), (3, Some(7)));
+
+    // The most recently freed slot is reused first
+    map.insert(20, 20).unwrap();
+    assert_eq!((map.free_slots(), map.highest_used_slot()), (2, Some(8)));
+
+    map.clear();
+    assert_eq!((map.free_slots(), map.highest_used_slot()), (0, None));
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::SEED;
+use fchashmap::FcHashMap;
+use rand_core::{RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+
+// The operations of a large, almost full map must run on a small stack. A recursion that grows
+// with the number of entries or the length of a cluster would overflow it. 16 KiB is the smallest
+// stack of a thread on Linux, smaller sizes are rounded up.
+#[test]
+fn small_stack() {
+    let mut map = Box::new(FcHashMap::<u32, u32, 4096>::new());
+    std::thread::Builder::new()
+        .stack_size(16 << 10)
+        .spawn(move || {
+            let mut rng = XorShiftRng::seed_from_u64(SEED);
+            for round in 0..100_000 {
+                let key = rng.next_u32() % 8192;
+                if round % 3 == 0 || map.len() > 4000 {
+                    map.remove(&key);
+                } else {
+                    map.insert(key, round).unwrap();
+                }
+                let _ = map.get(&rng.next_u32());
+            }
+            map.retain(|k, _| k % 2 == 0);
+            map.extract_if(|k, _| k % 4 == 0).count()
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+// clone_from() must copy a large map in place, clone() would place a copy on the stack
+#[test]
+fn clone_from_small_stack() {
+    let source: Box<FcHashMap<u32, u32, 4096>> = Box::new((0..3000).map(|i| (i, i)).collect());
+    let mut target = Box::new(FcHashMap::<u32, u32, 4096>::new());
+    target.insert(5000, 1).unwrap();
+    let target = std::thread::Builder::new()
+        .stack_size(16 << 10)
+        .spawn(move || {
+            target.clone_from(&source);
+            target
+        })
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(target.len(), 3000);
+    assert_eq!(target.get(&1234), Some(&1234));
+    assert_eq!(target.get(&5000), None);
+}
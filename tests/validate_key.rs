@@ -0,0 +1,33 @@
+use fchashmap::key::ValidateKey;
+use fchashmap::FcHashMap;
+
+#[derive(Debug, PartialEq, Eq)]
+struct DeviceId(u32);
+
+impl hash32::Hash for DeviceId {
+    fn hash<H: hash32::Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+impl ValidateKey for DeviceId {
+    fn validate(&self) -> bool {
+        self.0 != 0
+    }
+}
+
+// Invalid keys are rejected in debug builds and with the hardening feature, otherwise
+// insert_validated() inserts like insert()
+#[test]
+fn invalid_keys_rejected() {
+    let mut map = FcHashMap::<DeviceId, u8, 4>::new();
+    assert_eq!(map.insert_validated(DeviceId(7), 1), Ok(None));
+    let result = map.insert_validated(DeviceId(0), 2);
+    if cfg!(any(debug_assertions, feature = "hardening")) {
+        assert_eq!(result, Err((DeviceId(0), 2)));
+        assert_eq!(map.len(), 1);
+    } else {
+        assert_eq!(result, Ok(None));
+        assert_eq!(map.len(), 2);
+    }
+}